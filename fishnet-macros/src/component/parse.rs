@@ -3,14 +3,25 @@ use proc_macro2::{
     token_stream::IntoIter, Delimiter, Ident, Literal, Span, TokenStream, TokenTree,
 };
 use proc_macro_error::{abort, abort_call_site, emit_error};
-use quote::{quote, ToTokens, TokenStreamExt};
+use quote::{quote, quote_spanned, ToTokens, TokenStreamExt};
 
 #[derive(Debug)]
 pub struct ParsedComponent {
     is_pub: bool,
     name: String,
     args: TokenStream,
+    return_type: Option<TokenStream>,
     is_dyn: bool,
+    // how long the render is cached for, in milliseconds
+    cache: Option<u64>,
+    sse: bool,
+    render_route: bool,
+    // the htmx trigger loading a lazy component
+    lazy: Option<String>,
+    placeholder: Option<TokenStream>,
+    auth: Option<TokenStream>,
+    // builder calls configuring the wrapper element, e.g. `.tag("nav")`
+    wrapper: TokenStream,
     state: Option<ComponentState>,
     style: Option<ComponentStyle>,
     script: String,
@@ -21,13 +32,27 @@ pub struct ParsedComponent {
 }
 
 impl ParsedComponent {
-    fn new(name: &str, args: TokenStream, is_dyn: bool, is_pub: bool) -> Self {
+    fn new(
+        name: &str,
+        args: TokenStream,
+        return_type: Option<TokenStream>,
+        is_dyn: bool,
+        is_pub: bool,
+    ) -> Self {
         Self {
             name: name.to_string(),
             args,
+            return_type,
 
             is_dyn,
             is_pub,
+            cache: None,
+            sse: false,
+            render_route: false,
+            lazy: None,
+            placeholder: None,
+            auth: None,
+            wrapper: TokenStream::new(),
             state: None,
             style: None,
             script: String::new(),
@@ -79,10 +104,19 @@ impl ToTokens for ParsedComponent {
         let routes = self.routes.iter().map(|route| {
             let path = &route.path;
             let handler_name = &route.handler_name;
-            let method = &route.axum_method;
 
-            quote! {
-                .route(#path, routing::#method(#handler_name))
+            match &route.kind {
+                RouteKind::Method(method) => match &route.auth {
+                    Some(auth) => quote! {
+                        .route(#path, fishnet::auth::Auth::guard(&(#auth), routing::#method(#handler_name)))
+                    },
+                    None => quote! {
+                        .route(#path, routing::#method(#handler_name))
+                    },
+                },
+                RouteKind::WebSocket => quote! {
+                    .websocket(#path, #handler_name)
+                },
             }
         });
         let routes = quote! {
@@ -139,19 +173,76 @@ impl ToTokens for ParsedComponent {
         };
 
         let code = &self.render;
-        let render = match self.is_dyn {
-            false => quote! {
-                .render(|#state_ident| async move {
+        let (render_fn, cache) = match (self.return_type.is_some(), self.is_dyn, self.cache) {
+            (false, true, _) => (quote!(render_dynamic), TokenStream::new()),
+            (true, true, _) => (quote!(try_render_dynamic), TokenStream::new()),
+            (false, false, None) => (quote!(render), TokenStream::new()),
+            (true, false, None) => (quote!(try_render), TokenStream::new()),
+            (false, false, Some(millis)) => (
+                quote!(render_cached),
+                quote!(std::time::Duration::from_millis(#millis),),
+            ),
+            (true, false, Some(millis)) => (
+                quote!(try_render_cached),
+                quote!(std::time::Duration::from_millis(#millis),),
+            ),
+        };
+        let render = match &self.return_type {
+            None => quote! {
+                .#render_fn(#cache |#state_ident| async move {
                     #code
                 }.boxed())
             },
-            true => quote! {
-                .render_dynamic(|#state_ident| async move {
-                    #code
+            // the return type is needed for inferring the error type of `?` inside the body
+            Some(return_type) => quote! {
+                .#render_fn(#cache |#state_ident| async move {
+                    let render: #return_type = async move {
+                        #code
+                    }.await;
+                    render
                 }.boxed())
             },
         };
 
+        let sse = if self.sse {
+            quote! {
+                .sse_stream()
+            }
+        } else {
+            TokenStream::new()
+        };
+
+        let render_route = if self.render_route {
+            quote! {
+                .render_route()
+            }
+        } else {
+            TokenStream::new()
+        };
+
+        let lazy = match self.lazy.as_deref() {
+            Some(trigger) => {
+                let placeholder = match &self.placeholder {
+                    Some(placeholder) => placeholder.clone(),
+                    None => quote! { fishnet::Markup::default() },
+                };
+                match trigger {
+                    "revealed" => quote! { .lazy_revealed(#placeholder) },
+                    _ => quote! { .lazy(#placeholder) },
+                }
+            }
+            None => TokenStream::new(),
+        };
+
+        let auth = match &self.auth {
+            Some(auth) => quote! {
+                .require_auth(#auth)
+            },
+            None => TokenStream::new(),
+        };
+
+        let wrapper = &self.wrapper;
+
         let pub_ts = if self.is_pub {
             quote! { pub }
         } else {
@@ -171,6 +262,11 @@ impl ToTokens for ParsedComponent {
                     #script
                     #external_scripts
                     #runner
+                    #sse
+                    #render_route
+                    #lazy
+                    #auth
+                    #wrapper
                     #render
             }
         })
@@ -198,7 +294,14 @@ struct ComponentRoute {
     path: String,
     handler_name: Ident,
     handler: TokenStream,
-    axum_method: Ident,
+    kind: RouteKind,
+    auth: Option<TokenStream>,
+}
+
+#[derive(Debug)]
+enum RouteKind {
+    Method(Ident),
+    WebSocket,
 }
 
 #[derive(Debug)]
@@ -209,12 +312,16 @@ enum MacroTypes {
     Runner,
 }
 
-pub(crate) fn parse(input: TokenStream) -> ParsedComponent {
-    Parser::new(input, false).parse()
+pub(crate) fn parse(attr: TokenStream, input: TokenStream) -> ParsedComponent {
+    let mut parser = Parser::new(input, false);
+    parser.parse_attributes(attr);
+    parser.parse()
 }
 
-pub(crate) fn parse_dyn(input: TokenStream) -> ParsedComponent {
-    Parser::new(input, true).parse()
+pub(crate) fn parse_dyn(attr: TokenStream, input: TokenStream) -> ParsedComponent {
+    let mut parser = Parser::new(input, true);
+    parser.parse_attributes(attr);
+    parser.parse()
 }
 
 struct Parser {
@@ -230,6 +337,92 @@ impl Iterator for Parser {
     }
 }
 
+fn expect_string(value: &TokenStream) -> String {
+    let mut tokens = value.clone().into_iter();
+    match (tokens.next(), tokens.next()) {
+        (Some(TokenTree::Literal(lit)), None) => match StringLit::try_from(&lit) {
+            Ok(lit) => lit.value().to_string(),
+            Err(_) => abort!(lit, "expected a string literal"),
+        },
+        _ => abort!(value, "expected a string literal"),
+    }
+}
+
+// the tokens, if they are nothing but a string literal
+fn string_literal(value: &TokenStream) -> Option<Literal> {
+    let mut tokens = value.clone().into_iter();
+    match (tokens.next(), tokens.next()) {
+        (Some(TokenTree::Literal(lit)), None) if StringLit::try_from(&lit).is_ok() => Some(lit),
+        _ => None,
+    }
+}
+
+// a constant using one of the validators of the wrapper on a literal, failing to compile if it isn't valid.
+// this keeps the rules in one place, while literals still fail at compile time instead of on the first render.
+fn const_check(validator: &str, lit: &Literal) -> TokenStream {
+    let validator = Ident::new(validator, lit.span());
+    quote_spanned! {lit.span()=>
+        const _: () = if let ::core::option::Option::Some(error) = fishnet::component::#validator(#lit) {
+            ::core::panic!("{}", error)
+        };
+    }
+}
+
+fn checked_tag(value: TokenStream) -> TokenStream {
+    let Some(lit) = string_literal(&value) else {
+        return value;
+    };
+    let check = const_check("tag_error", &lit);
+    quote! {{ #check #value }}
+}
+
+// checks the names of literal attributes, e.g. `[("aria-label", "main")]`
+fn checked_attrs(value: TokenStream) -> TokenStream {
+    let mut tokens = value.clone().into_iter();
+    let attrs = match (tokens.next(), tokens.next()) {
+        (Some(TokenTree::Group(group)), None) if group.delimiter() == Delimiter::Bracket => {
+            group.stream()
+        }
+        _ => return value,
+    };
+
+    let mut checks = TokenStream::new();
+    for attr in attrs {
+        let TokenTree::Group(attr) = attr else {
+            continue;
+        };
+        if attr.delimiter() != Delimiter::Parenthesis {
+            continue;
+        }
+        let name: TokenStream = attr
+            .stream()
+            .into_iter()
+            .take_while(|token| !matches!(token, TokenTree::Punct(punct) if punct.as_char() == ','))
+            .collect();
+        if let Some(lit) = string_literal(&name) {
+            checks.extend(const_check("attr_error", &lit));
+        }
+    }
+    quote! {{ #checks #value }}
+}
+
+// parse a duration like `60s` into milliseconds
+fn parse_duration(duration: &str) -> Option<u64> {
+    let duration = duration.trim();
+    let unit_start = duration.find(|c: char| !c.is_ascii_digit())?;
+    let (value, unit) = duration.split_at(unit_start);
+    let value: u64 = value.parse().ok()?;
+
+    let factor = match unit.trim() {
+        "ms" => 1,
+        "s" => 1000,
+        "m" => 60 * 1000,
+        "h" => 60 * 60 * 1000,
+        _ => return None,
+    };
+    value.checked_mul(factor)
+}
+
 fn to_pascal(name: &str) -> String {
     let mut name = name.chars();
     let mut next_upper = true;
@@ -284,20 +477,125 @@ impl Parser {
             _ => abort_call_site!("expected function arguments"),
         };
 
-        let fn_inner = match input.next() {
-            Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => {
-                group.stream()
+        // components returning a `Result` can fail to render
+        let mut return_type = None;
+        let fn_inner = loop {
+            match input.next() {
+                Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => {
+                    break group.stream();
+                }
+                Some(TokenTree::Punct(punct)) if punct.as_char() == '-' => {
+                    match input.next() {
+                        Some(TokenTree::Punct(punct)) if punct.as_char() == '>' => {}
+                        _ => abort!(punct, "expected '->'"),
+                    }
+                    return_type = Some(TokenStream::new());
+                }
+                Some(token) => match return_type.as_mut() {
+                    Some(return_type) => return_type.append(token),
+                    None => abort!(token, "expected function body"),
+                },
+                None => abort_call_site!("expected function body"),
             }
-            _ => abort_call_site!("expected function body"),
         };
+        if let Some(return_type) = &return_type {
+            if return_type.is_empty() {
+                abort_call_site!("expected return type after '->'");
+            }
+        }
 
         Self {
             input: fn_inner.into_iter(),
-            parsed: ParsedComponent::new(&name, fn_args, is_dyn, is_pub),
+            parsed: ParsedComponent::new(&name, fn_args, return_type, is_dyn, is_pub),
             last_ident: None,
         }
     }
 
+    // arguments of the attribute itself, e.g. `#[component(cache = "60s")]`
+    fn parse_attributes(&mut self, attr: TokenStream) {
+        let mut attr = attr.into_iter().peekable();
+        while let Some(token) = attr.next() {
+            let name = match token {
+                TokenTree::Ident(ref ident) => ident.to_string(),
+                TokenTree::Punct(ref punct) if punct.as_char() == ',' => continue,
+                _ => abort!(token, "expected component argument"),
+            };
+
+            // flags don't have a value
+            match attr.peek() {
+                Some(TokenTree::Punct(ref punct)) if punct.as_char() == ',' => {
+                    self.parse_flag(&token, &name);
+                    continue;
+                }
+                None => {
+                    self.parse_flag(&token, &name);
+                    continue;
+                }
+                _ => {}
+            }
+
+            match attr.next() {
+                Some(TokenTree::Punct(ref punct)) if punct.as_char() == '=' => {}
+                _ => abort!(token, "expected '=' after '{}'", name),
+            }
+            // values are expressions, which end at the next top level comma
+            let mut value = TokenStream::new();
+            while let Some(next) = attr.peek() {
+                if matches!(next, TokenTree::Punct(ref punct) if punct.as_char() == ',') {
+                    break;
+                }
+                value.extend(attr.next());
+            }
+            if value.is_empty() {
+                abort!(token, "expected a value for '{}'", name);
+            }
+
+            match name.as_str() {
+                "auth" => self.parsed.auth = Some(value),
+                "lazy" => match expect_string(&value).as_str() {
+                    trigger @ ("load" | "revealed") => self.parsed.lazy = Some(trigger.to_string()),
+                    _ => abort!(value, "expected \"load\" or \"revealed\""),
+                },
+                "placeholder" => self.parsed.placeholder = Some(value),
+                "tag" => {
+                    let value = checked_tag(value);
+                    self.parsed.wrapper.extend(quote! { .tag(#value) })
+                }
+                "class" => self.parsed.wrapper.extend(quote! { .class(#value) }),
+                "attrs" => {
+                    let value = checked_attrs(value);
+                    self.parsed.wrapper.extend(quote! { .attrs(#value) })
+                }
+                "cache" if self.parsed.is_dyn => abort!(
+                    token,
+                    "dynamic components are rendered on every page render, so they can't be cached"
+                ),
+                "cache" => match parse_duration(&expect_string(&value)) {
+                    Some(millis) => self.parsed.cache = Some(millis),
+                    None => abort!(
+                        value,
+                        "invalid duration, expected something like \"500ms\", \"60s\", \"5m\" or \"1h\""
+                    ),
+                },
+                _ => abort!(token, "unknown component argument '{}'", name),
+            }
+        }
+
+        if self.parsed.placeholder.is_some() && self.parsed.lazy.is_none() {
+            abort_call_site!("a placeholder can only be used with lazy components, add `lazy`");
+        }
+    }
+
+    fn parse_flag(&mut self, token: &TokenTree, name: &str) {
+        match name {
+            "sse" => self.parsed.sse = true,
+            "render_route" => self.parsed.render_route = true,
+            "lazy" => self.parsed.lazy = Some("load".to_string()),
+            "fragment" => self.parsed.wrapper.extend(quote! { .fragment() }),
+            _ => abort!(token, "unknown component flag '{}'", name),
+        }
+    }
+
     fn peek(&mut self) -> Option<TokenTree> {
         self.input.clone().next()
     }
@@ -404,6 +702,9 @@ impl Parser {
                                 {
                                     self.parse_route(inner);
                                 }
+                                Some(TokenTree::Ident(ref ident)) if *ident == "websocket" => {
+                                    self.parse_websocket(inner);
+                                }
                                 _ => {
                                     for token in collected {
                                         self.add_to_code(token);
@@ -614,13 +915,57 @@ impl Parser {
             }
         };
 
+        // an optional guard after the method, e.g. `#[route("/", POST, auth = admin())]`
+        let auth = match (args.get(4), args.get(5)) {
+            (None, _) => None,
+            (Some(TokenTree::Ident(ref ident)), Some(TokenTree::Punct(ref punct)))
+                if *ident == "auth" && punct.as_char() == '=' && args.len() > 6 =>
+            {
+                Some(args[6..].iter().cloned().collect::<TokenStream>())
+            }
+            (Some(other), _) => {
+                emit_error!(other, "expected `auth = ...`");
+                return;
+            }
+        };
+
+        let handler = self.parse_async_fn();
+
+        self.parsed.routes.push(ComponentRoute {
+            path,
+            handler_name: handler.0,
+            handler: handler.1,
+            kind: RouteKind::Method(method),
+            auth,
+        });
+    }
+
+    fn parse_websocket(&mut self, mut inner: IntoIter) {
+        let path = match inner.next() {
+            Some(TokenTree::Group(ref group)) => match group.stream().into_iter().next() {
+                Some(TokenTree::Literal(ref lit)) => match StringLit::try_from(lit) {
+                    Ok(lit) => lit.value().to_string(),
+                    Err(_) => lit.to_string(),
+                },
+                other => {
+                    emit_error!(other, "expected string literal for websocket path");
+                    return;
+                }
+            },
+            _ => {
+                emit_error!(self.peek(), "missing websocket path");
+                return;
+            }
+        };
+
         let handler = self.parse_async_fn();
 
         self.parsed.routes.push(ComponentRoute {
             path,
             handler_name: handler.0,
             handler: handler.1,
-            axum_method: method,
+            kind: RouteKind::WebSocket,
+            auth: None,
         });
     }
 
@@ -0,0 +1,325 @@
+use litrs::{IntegerLit, StringLit};
+use proc_macro2::{Delimiter, Ident, TokenStream, TokenTree};
+use proc_macro_error::{abort, abort_call_site};
+use quote::{quote, ToTokens};
+
+#[derive(Debug)]
+pub struct ParsedForm {
+    name: Ident,
+    submit: Option<String>,
+    fields: Vec<FormField>,
+}
+
+#[derive(Debug)]
+struct FormField {
+    ident: Ident,
+    ty: TokenStream,
+
+    label: Option<String>,
+    input: Option<String>,
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+    validate: Option<TokenStream>,
+}
+
+impl ToTokens for ParsedForm {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let name = &self.name;
+        let form_name = to_kebab(&name.to_string());
+
+        let submit = match &self.submit {
+            Some(submit) => quote! { const SUBMIT: &'static str = #submit; },
+            None => TokenStream::new(),
+        };
+
+        let field_infos = self.fields.iter().map(|field| {
+            let field_name = field.ident.to_string();
+            let ty = &field.ty;
+
+            let input = match &field.input {
+                Some(input) => quote!(#input),
+                None => quote!(<#ty as fishnet::form::FormField>::INPUT),
+            };
+            let label = field.label.as_ref().map(|label| quote!(.label(#label)));
+            let min_length = field.min_length.map(|min| quote!(.min_length(#min)));
+            let max_length = field.max_length.map(|max| quote!(.max_length(#max)));
+
+            quote! {
+                fishnet::form::FormFieldInfo::new(
+                    #field_name,
+                    #input,
+                    <#ty as fishnet::form::FormField>::REQUIRED,
+                )
+                #label
+                #min_length
+                #max_length
+            }
+        });
+
+        let parse_fields = self.fields.iter().enumerate().map(|(i, field)| {
+            let ident = &field.ident;
+            let ty = &field.ty;
+            let field_name = ident.to_string();
+
+            let validate = field.validate.as_ref().map(|validate| {
+                quote! {
+                    if let Some(value) = &#ident {
+                        if let Err(error) = #validate(value) {
+                            errors.add(#field_name, error);
+                        }
+                    }
+                }
+            });
+
+            quote! {
+                let #ident: Option<#ty> = fields[#i].parse(values, &mut errors);
+                #validate
+            }
+        });
+
+        let idents = self
+            .fields
+            .iter()
+            .map(|field| &field.ident)
+            .collect::<Vec<_>>();
+
+        tokens.extend(quote! {
+            impl fishnet::form::Form for #name {
+                const NAME: &'static str = #form_name;
+                #submit
+
+                fn fields() -> Vec<fishnet::form::FormFieldInfo> {
+                    vec![#(#field_infos),*]
+                }
+
+                fn parse(
+                    values: &fishnet::form::FormValues,
+                ) -> Result<Self, fishnet::form::FormErrors> {
+                    let fields = Self::fields();
+                    let mut errors = fishnet::form::FormErrors::new();
+
+                    #(#parse_fields)*
+
+                    if !errors.is_empty() {
+                        return Err(errors);
+                    }
+                    match (#(#idents,)*) {
+                        (#(Some(#idents),)*) => Ok(Self { #(#idents),* }),
+                        _ => Err(errors),
+                    }
+                }
+            }
+        });
+    }
+}
+
+pub(crate) fn parse(input: TokenStream) -> ParsedForm {
+    let mut input = input.into_iter().peekable();
+
+    let mut submit = None;
+    let name = loop {
+        match input.next() {
+            Some(TokenTree::Punct(punct)) if punct.as_char() == '#' => match input.next() {
+                Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Bracket => {
+                    for (key, value) in parse_form_attribute(group.stream()) {
+                        match key.to_string().as_str() {
+                            "submit" => submit = Some(expect_string(&value)),
+                            _ => abort!(key, "unknown form argument '{}'", key),
+                        }
+                    }
+                }
+                _ => abort!(punct, "expected attribute"),
+            },
+            Some(TokenTree::Ident(ident)) if ident == "pub" => {
+                // skip restricted visibility like `pub(crate)`
+                if let Some(TokenTree::Group(group)) = input.peek() {
+                    if group.delimiter() == Delimiter::Parenthesis {
+                        input.next();
+                    }
+                }
+            }
+            Some(TokenTree::Ident(ident)) if ident == "struct" => match input.next() {
+                Some(TokenTree::Ident(name)) => break name,
+                _ => abort!(ident, "expected struct name"),
+            },
+            Some(token) => abort!(token, "forms can only be derived for structs"),
+            None => abort_call_site!("expected struct definition"),
+        }
+    };
+
+    let fields = match input.next() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => {
+            parse_fields(group.stream())
+        }
+        Some(TokenTree::Punct(punct)) if punct.as_char() == '<' => {
+            abort!(punct, "forms can't have generic parameters")
+        }
+        Some(token) => abort!(token, "forms need to have named fields"),
+        None => abort!(name, "expected struct fields"),
+    };
+
+    ParsedForm {
+        name,
+        submit,
+        fields,
+    }
+}
+
+fn parse_fields(input: TokenStream) -> Vec<FormField> {
+    let mut fields = Vec::new();
+    let mut input = input.into_iter().peekable();
+
+    while input.peek().is_some() {
+        let mut field_attributes = Vec::new();
+        let ident = loop {
+            match input.next() {
+                Some(TokenTree::Punct(punct)) if punct.as_char() == '#' => match input.next() {
+                    Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Bracket => {
+                        field_attributes.extend(parse_form_attribute(group.stream()));
+                    }
+                    _ => abort!(punct, "expected attribute"),
+                },
+                Some(TokenTree::Ident(ident)) if ident == "pub" => {
+                    if let Some(TokenTree::Group(group)) = input.peek() {
+                        if group.delimiter() == Delimiter::Parenthesis {
+                            input.next();
+                        }
+                    }
+                }
+                Some(TokenTree::Ident(ident)) => break ident,
+                Some(token) => abort!(token, "expected field name"),
+                None => abort_call_site!("expected field name"),
+            }
+        };
+
+        match input.next() {
+            Some(TokenTree::Punct(punct)) if punct.as_char() == ':' => {}
+            _ => abort!(ident, "expected ':' after field name"),
+        }
+
+        // the type ends at the next comma that is not inside of generic arguments
+        let mut ty = TokenStream::new();
+        let mut depth = 0;
+        loop {
+            match input.next() {
+                Some(TokenTree::Punct(punct)) if punct.as_char() == ',' && depth == 0 => break,
+                Some(token) => {
+                    if let TokenTree::Punct(punct) = &token {
+                        match punct.as_char() {
+                            '<' => depth += 1,
+                            '>' => depth -= 1,
+                            _ => {}
+                        }
+                    }
+                    ty.extend([token]);
+                }
+                None => break,
+            }
+        }
+        if ty.is_empty() {
+            abort!(ident, "expected field type");
+        }
+
+        let mut field = FormField {
+            ident,
+            ty,
+            label: None,
+            input: None,
+            min_length: None,
+            max_length: None,
+            validate: None,
+        };
+        for (key, value) in field_attributes {
+            match key.to_string().as_str() {
+                "label" => field.label = Some(expect_string(&value)),
+                "input" => field.input = Some(expect_string(&value)),
+                "min_length" => field.min_length = Some(expect_usize(&value)),
+                "max_length" => field.max_length = Some(expect_usize(&value)),
+                "validate" => field.validate = Some(value),
+                _ => abort!(key, "unknown field argument '{}'", key),
+            }
+        }
+        fields.push(field);
+    }
+
+    fields
+}
+
+// the `key = value` pairs of a `#[form(...)]` attribute, other attributes (e.g. doc comments) are ignored.
+fn parse_form_attribute(attribute: TokenStream) -> Vec<(Ident, TokenStream)> {
+    let mut attribute = attribute.into_iter();
+    match attribute.next() {
+        Some(TokenTree::Ident(ident)) if ident == "form" => {}
+        _ => return Vec::new(),
+    }
+    let args = match attribute.next() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis => {
+            group.stream()
+        }
+        other => abort!(other, "expected arguments in parentheses"),
+    };
+
+    let mut pairs = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(key) = args.next() {
+        let key = match key {
+            TokenTree::Ident(ident) => ident,
+            other => abort!(other, "expected argument name"),
+        };
+        match args.next() {
+            Some(TokenTree::Punct(punct)) if punct.as_char() == '=' => {}
+            _ => abort!(key, "expected '=' after '{}'", key),
+        }
+
+        let mut value = TokenStream::new();
+        for token in args.by_ref() {
+            match token {
+                TokenTree::Punct(ref punct) if punct.as_char() == ',' => break,
+                token => value.extend([token]),
+            }
+        }
+        if value.is_empty() {
+            abort!(key, "expected a value for '{}'", key);
+        }
+        pairs.push((key, value));
+    }
+
+    pairs
+}
+
+fn expect_string(value: &TokenStream) -> String {
+    match value.clone().into_iter().next() {
+        Some(TokenTree::Literal(lit)) => match StringLit::try_from(&lit) {
+            Ok(lit) => lit.value().to_string(),
+            Err(_) => abort!(lit, "expected a string literal"),
+        },
+        other => abort!(other, "expected a string literal"),
+    }
+}
+
+fn expect_usize(value: &TokenStream) -> usize {
+    match value.clone().into_iter().next() {
+        Some(TokenTree::Literal(lit)) => match IntegerLit::try_from(&lit) {
+            Ok(int) => int
+                .value::<usize>()
+                .unwrap_or_else(|| abort!(lit, "number too large")),
+            Err(_) => abort!(lit, "expected an integer literal"),
+        },
+        other => abort!(other, "expected an integer literal"),
+    }
+}
+
+fn to_kebab(name: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if i != 0 {
+                out.push('-');
+            }
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
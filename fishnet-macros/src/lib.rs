@@ -2,6 +2,7 @@ mod css;
 use crate::css::ToFmt;
 
 mod component;
+mod form;
 
 use nanoid::nanoid;
 use proc_macro2::{TokenStream, TokenTree};
@@ -62,12 +63,12 @@ pub fn css(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
 #[proc_macro_attribute]
 #[proc_macro_error]
 pub fn component(
-    _attr: proc_macro::TokenStream,
+    attr: proc_macro::TokenStream,
     item: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
     let item: TokenStream = item.into();
 
-    let component = component::parse(item);
+    let component = component::parse(attr.into(), item);
 
     let out = quote!(
             #component
@@ -79,12 +80,12 @@ pub fn component(
 #[proc_macro_attribute]
 #[proc_macro_error]
 pub fn dyn_component(
-    _attr: proc_macro::TokenStream,
+    attr: proc_macro::TokenStream,
     item: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
     let item: TokenStream = item.into();
 
-    let component = component::parse_dyn(item);
+    let component = component::parse_dyn(attr.into(), item);
 
     let out = quote!(
             #component
@@ -93,6 +94,16 @@ pub fn dyn_component(
     out.into()
 }
 
+#[proc_macro_derive(Form, attributes(form))]
+#[proc_macro_error]
+pub fn derive_form(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let item: TokenStream = item.into();
+
+    let form = form::parse(item);
+
+    quote!(#form).into()
+}
+
 #[proc_macro]
 #[proc_macro_error]
 pub fn const_nanoid(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
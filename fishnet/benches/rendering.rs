@@ -19,7 +19,7 @@ macro_rules! make_page {
 
             let built = BuiltPage::new(page, "/").await;
 
-            BuiltPage::render(Extension(built.0.clone())).await;
+            BuiltPage::render(Extension(built.0.clone())).await.unwrap();
 
             built
         });
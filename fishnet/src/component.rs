@@ -7,23 +7,39 @@ pub use build::{BuildableComponent, BuiltComponent, ComponentBuildResult};
 
 mod render;
 use render::ContentRenderer;
+pub(crate) use render::Invalidation;
+
+mod sse;
+use sse::SseChannel;
+
+mod wrapper;
+use wrapper::Wrapper;
+#[doc(hidden)]
+pub use wrapper::{attr_error, tag_error};
 
 #[doc(hidden)]
 pub mod fake_macros;
 
+use crate::auth::Auth;
 use crate::css::StyleFragment;
 use crate::js::ScriptType;
+use crate::page::RenderError;
 use crate::routes::ComponentRoute;
 
 use axum::{
-    body::Body, http::Request, response::IntoResponse, routing::method_routing::MethodRouter,
-    Router,
+    body::Body,
+    extract::ws::{WebSocket, WebSocketUpgrade},
+    http::{HeaderName, Request},
+    response::{IntoResponse, IntoResponseParts},
+    routing::{get, method_routing::MethodRouter},
+    Extension, Router,
 };
 use core::convert::Infallible;
-use futures::future::BoxFuture;
+use futures::future::{BoxFuture, FutureExt};
 use maud::Markup;
-use std::{fmt::Debug, marker::PhantomData, ops::Deref, sync::Arc};
+use std::{fmt::Debug, future::Future, marker::PhantomData, ops::Deref, sync::Arc, time::Duration};
 use tower_service::Service;
+use tracing::warn;
 
 #[derive(Debug, Clone)]
 pub struct ComponentState<ST>
@@ -31,6 +47,9 @@ where
     ST: Clone + Send + Sync,
 {
     api_route: ComponentRoute,
+    dom_id: Arc<str>,
+    invalidation: Invalidation,
+    sse: Option<SseChannel>,
     state: ST,
 }
 impl<ST> ComponentState<ST>
@@ -40,6 +59,137 @@ where
     pub fn endpoint(&self) -> &str {
         self.api_route.as_str()
     }
+
+    /// the id of the element the component is rendered into.
+    ///
+    /// it is unique for every [`c!`](crate::c!) rendering the component (and each keyed instance of it), unless a different id was set
+    /// using [`attr`](Component::attr). components rendered as a [`fragment`](Component::fragment) don't have an element,
+    /// so there is nothing with this id on the page.
+    pub fn dom_id(&self) -> &str {
+        &self.dom_id
+    }
+
+    /// a css selector for the element the component is rendered into, meant to be used with `hx-target`.
+    pub fn target(&self) -> String {
+        format!("#{}", self.dom_id)
+    }
+
+    /// htmx response headers that swap the response into the element the component is rendered into,
+    /// replacing the content of the component regardless of the `hx-target` of the element that sent the request.
+    ///
+    /// ```rust
+    /// use axum::response::IntoResponse;
+    /// use fishnet::component::prelude::*;
+    ///
+    /// type Likes = ComponentState<Arc<Mutex<usize>>>;
+    ///
+    /// fn like_counter(state: &Likes, likes: usize) -> Markup {
+    ///     html! {
+    ///         (likes) " likes"
+    ///         button hx-post=(state.endpoint()) { "like" }
+    ///     }
+    /// }
+    ///
+    /// #[component]
+    /// fn like_button() {
+    ///     #[route("/", POST)]
+    ///     async fn like(state: Extension<Likes>) -> impl IntoResponse {
+    ///         let mut likes = state.lock().await;
+    ///         *likes += 1;
+    ///         (state.swap_self(), like_counter(&state, *likes))
+    ///     }
+    ///
+    ///     let state = state!(Arc<Mutex<usize>>);
+    ///     let likes = *state.lock().await;
+    ///     like_counter(&state, likes)
+    /// }
+    /// ```
+    pub fn swap_self(&self) -> impl IntoResponseParts {
+        [
+            (HeaderName::from_static("hx-retarget"), self.target()),
+            (
+                HeaderName::from_static("hx-reswap"),
+                "innerHTML".to_string(),
+            ),
+        ]
+    }
+
+    /// the endpoint of the server-sent event stream of the component (see [`Component::sse_stream`]).
+    ///
+    /// this is meant to be used with the `sse-connect` attribute of the htmx sse extension.
+    pub fn sse_endpoint(&self) -> String {
+        format!("{}/sse", self.api_route.as_str())
+    }
+
+    /// the url of the [render route](Component::render_route) of the component, responding with its current content.
+    pub fn render_url(&self) -> String {
+        format!("{}/render", self.api_route.as_str())
+    }
+
+    /// send markup to everyone connected to the [sse stream](Component::sse_stream) of the component.
+    ///
+    /// the markup is sent as an unnamed event, which htmx receives as `message` (`sse-swap="message"`).
+    /// returns the number of clients it was sent to.
+    pub fn broadcast(&self, markup: Markup) -> usize {
+        self.send_sse(None, markup)
+    }
+
+    /// send markup as a named event to everyone connected to the [sse stream](Component::sse_stream) of the component.
+    ///
+    /// returns the number of clients it was sent to.
+    pub fn broadcast_event(&self, event: &str, markup: Markup) -> usize {
+        self.send_sse(Some(event), markup)
+    }
+
+    fn send_sse(&self, event: Option<&str>, markup: Markup) -> usize {
+        match &self.sse {
+            Some(channel) => channel.send(event, markup),
+            None => {
+                warn!(
+                    route = self.api_route.as_str(),
+                    "tried to broadcast from a component without an sse stream"
+                );
+                0
+            }
+        }
+    }
+
+    /// mark the cached render of the component as outdated.
+    ///
+    /// static components are only rendered once and then served from a cache. after invalidating, the component is
+    /// rendered again on the next page render and cached again afterwards. static components containing this
+    /// component are invalidated as well, since their cached render includes it.
+    ///
+    /// this is useful for components that get their content from a runner:
+    /// ```rust
+    /// use fishnet::component::prelude::*;
+    /// use std::sync::Arc;
+    /// use tokio::sync::RwLock;
+    ///
+    /// #[component]
+    /// async fn latest_posts() {
+    ///     let state = state_init!(Arc::new(RwLock::new(Vec::<String>::new())));
+    ///
+    ///     runner!({
+    ///         loop {
+    ///             tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+    ///             state.write().await.push("a new post".to_string());
+    ///             state.invalidate();
+    ///         }
+    ///     });
+    ///
+    ///     let posts = state.read().await.clone();
+    ///     html! {
+    ///         @for post in posts {
+    ///             p { (post) }
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    /// invalidating a dynamic component does nothing to the component itself, it is rendered on every page render anyway.
+    pub fn invalidate(&self) {
+        self.invalidation.invalidate();
+    }
 }
 impl<ST> Deref for ComponentState<ST>
 where
@@ -51,6 +201,12 @@ where
     }
 }
 
+// how a lazy component is loaded (see [`Component::lazy`])
+struct Lazy {
+    trigger: &'static str,
+    placeholder: Markup,
+}
+
 type ComponentRunner<ST> =
     Box<dyn FnOnce(ComponentState<ST>) -> BoxFuture<'static, ()> + Send + Sync + 'static>;
 
@@ -71,6 +227,12 @@ where
     name: Arc<str>,
     id: Arc<str>,
     is_dynamic: bool,
+    max_age: Option<Duration>,
+    sse: bool,
+    render_route: bool,
+    lazy: Option<Lazy>,
+    auth: Option<Auth>,
+    wrapper: Wrapper,
 
     renderer: Option<ContentRenderer<ST>>,
 
@@ -93,6 +255,12 @@ impl Component<NoRenderer, NoState, ()> {
             id: Arc::from(id),
 
             is_dynamic: false,
+            max_age: None,
+            sse: false,
+            render_route: false,
+            lazy: None,
+            auth: None,
+            wrapper: Wrapper::default(),
 
             state: (),
             router: None,
@@ -134,6 +302,202 @@ where
         self
     }
 
+    /// Add a websocket route to the component.
+    ///
+    /// Requests to the route are upgraded to a websocket, which is then passed to the handler together with the state of the component.
+    /// ```rust
+    /// use fishnet::component::prelude::*;
+    ///
+    /// #[component]
+    /// async fn echo() {
+    ///     let state = state!(());
+    ///
+    ///     #[websocket("/ws")]
+    ///     async fn echo_socket(mut socket: WebSocket, _state: ComponentState<()>) {
+    ///         while let Some(Ok(message)) = socket.recv().await {
+    ///             if socket.send(message).await.is_err() {
+    ///                 break;
+    ///             }
+    ///         }
+    ///     }
+    ///
+    ///     html! {
+    ///         div hx-ext="ws" ws-connect=(format!("{}/ws", state.endpoint())) {}
+    ///     }
+    /// }
+    /// ```
+    pub fn websocket<F, Fut>(self, path: &str, handler: F) -> Self
+    where
+        ST: 'static,
+        F: Fn(WebSocket, ComponentState<ST>) -> Fut + Clone + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let upgrade = move |upgrade: WebSocketUpgrade,
+                            Extension(state): Extension<ComponentState<ST>>| {
+            let handler = handler.clone();
+            async move { upgrade.on_upgrade(move |socket| handler(socket, state)) }
+        };
+
+        self.route(path, get(upgrade))
+    }
+
+    /// Add a server-sent event stream to the component.
+    ///
+    /// Clients can connect to the stream at [`sse_endpoint`](ComponentState::sse_endpoint) and receive everything that is sent
+    /// using [`broadcast`](ComponentState::broadcast), e.g. from a runner or an api route. Pages using the component automatically
+    /// include the [htmx sse extension](https://htmx.org/extensions/server-sent-events/).
+    /// ```rust
+    /// use fishnet::component::prelude::*;
+    ///
+    /// #[component(sse)]
+    /// async fn clock() {
+    ///     let state = state!(());
+    ///
+    ///     runner!({
+    ///         loop {
+    ///             tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    ///             state.broadcast(html! { (format!("{:?}", std::time::SystemTime::now())) });
+    ///         }
+    ///     });
+    ///
+    ///     html! {
+    ///         div hx-ext="sse" sse-connect=(state.sse_endpoint()) sse-swap="message" { "loading..." }
+    ///     }
+    /// }
+    /// ```
+    pub fn sse_stream(mut self) -> Self {
+        self.sse = true;
+        self
+    }
+
+    /// Add a route rendering the content of the component with its current state.
+    ///
+    /// The route is served at [`render_url`](ComponentState::render_url) and responds with the markup inside of the wrapper
+    /// element, so it can be used for refreshing the component without writing a route for it.
+    /// The component is rendered with the [slots](crate::c!#slots) of its latest render and the
+    /// [path parameters](crate::page::RequestContext::params) of the page the request was sent from. For components shared by
+    /// all parameters of a pattern page, those are read from the `HX-Current-URL` header htmx sends (or the `Referer`).
+    /// ```rust
+    /// use fishnet::component::prelude::*;
+    ///
+    /// #[component(render_route)]
+    /// async fn server_time() {
+    ///     let state = state!(());
+    ///
+    ///     html! {
+    ///         div hx-get=(state.render_url()) hx-trigger="every 10s" hx-target=(state.target()) {
+    ///             (format!("{:?}", std::time::SystemTime::now()))
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn render_route(mut self) -> Self {
+        self.render_route = true;
+        self
+    }
+
+    /// Render the component after the page has loaded instead of during the page render.
+    ///
+    /// The page contains the placeholder until htmx requests the content from a route of the component, so slow components
+    /// don't hold up the rest of the page. The content is cached just like the content of any other component and the
+    /// component doesn't force its parents to be dynamic, even if it is dynamic itself. [Slots](crate::c!#slots) are not
+    /// available within lazy components.
+    /// ```rust
+    /// use fishnet::component::prelude::*;
+    ///
+    /// #[component(lazy, placeholder = html! { "loading..." })]
+    /// async fn slow_statistics() {
+    ///     tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    ///
+    ///     html! {
+    ///         "42 visitors today"
+    ///     }
+    /// }
+    /// ```
+    pub fn lazy(mut self, placeholder: Markup) -> Self {
+        self.lazy = Some(Lazy {
+            trigger: "load",
+            placeholder,
+        });
+        self
+    }
+
+    /// Like [`lazy`](Component::lazy), but the component is only loaded once it is scrolled into view.
+    pub fn lazy_revealed(mut self, placeholder: Markup) -> Self {
+        self.lazy = Some(Lazy {
+            trigger: "revealed",
+            placeholder,
+        });
+        self
+    }
+
+    /// Only render the component for authenticated visitors and reject unauthorized requests to its routes.
+    ///
+    /// Unauthorized visitors see the [fallback](Auth::fallback) of the guard instead. Since this depends on the visitor,
+    /// the component is always rendered dynamically. See the [`auth`](crate::auth) module for more.
+    pub fn require_auth(mut self, auth: Auth) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    /// Set the element the component is rendered into, defaults to `div`.
+    ///
+    /// ```rust
+    /// use fishnet::component::prelude::*;
+    ///
+    /// #[component(tag = "nav", class = "sidebar", attrs = [("aria-label", "main")])]
+    /// async fn navigation() {
+    ///     html! {
+    ///         a href="/" { "home" }
+    ///     }
+    /// }
+    /// ```
+    /// # Panics
+    /// Panics if the tag is not a valid element name or is an element that can't have content (like `img`).
+    pub fn tag(mut self, tag: &str) -> Self {
+        self.wrapper.set_tag(tag);
+        self
+    }
+
+    /// Add a class to the element the component is rendered into.
+    ///
+    /// The element always has the class the [style](Component::style) of the component is scoped to.
+    pub fn class(mut self, class: &str) -> Self {
+        self.wrapper.add_class(class);
+        self
+    }
+
+    /// Set an attribute (e.g. an `id` or `aria-*` attributes) on the element the component is rendered into.
+    ///
+    /// # Panics
+    /// Panics if the name is not a valid attribute name or is `class` (use [`class`](Component::class) instead).
+    pub fn attr(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.wrapper.set_attr(name, value.into());
+        self
+    }
+
+    /// Set multiple attributes on the element the component is rendered into, see [`attr`](Component::attr).
+    pub fn attrs<I, K, V>(mut self, attrs: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: Into<String>,
+    {
+        for (name, value) in attrs {
+            self.wrapper.set_attr(name.as_ref(), value.into());
+        }
+        self
+    }
+
+    /// Render the component without a wrapping element.
+    ///
+    /// This is useful for components that render e.g. a single list item or table row. Since there is no element carrying the
+    /// class the [style](Component::style) of the component is scoped to, the style has to target the rendered elements itself.
+    pub fn fragment(mut self) -> Self {
+        self.wrapper.set_fragment();
+        self
+    }
+
     pub fn nest_service<T>(mut self, path: &str, service: T) -> Self
     where
         T: Service<Request<Body>, Error = Infallible> + Clone + Send + 'static,
@@ -157,41 +521,103 @@ where
         ST: Clone + Send + Sync + 'static,
         C: Fn(ComponentState<ST>) -> BoxFuture<'static, Markup> + Send + Sync + 'static,
     {
-        Component::<HasRenderer, S, ST> {
-            name: self.name,
-            id: self.id,
+        self.try_render(move |state| renderer(state).map(Ok::<_, Infallible>).boxed())
+    }
 
-            is_dynamic: false,
+    pub fn render_dynamic<C>(self, renderer: C) -> impl BuildableComponent
+    where
+        ST: Clone + Send + Sync + 'static,
+        C: Fn(ComponentState<ST>) -> BoxFuture<'static, Markup> + Send + Sync + 'static,
+    {
+        self.try_render_dynamic(move |state| renderer(state).map(Ok::<_, Infallible>).boxed())
+    }
 
-            state: self.state,
-            router: self.router,
+    /// Add a renderer whose render is cached for the given duration.
+    ///
+    /// The component is rendered again on the first page render after its render got older than `max_age`.
+    /// In between it is treated like a static component, so static components containing it stay cached as well (until it expires).
+    pub fn render_cached<C>(self, max_age: Duration, renderer: C) -> impl BuildableComponent
+    where
+        ST: Clone + Send + Sync + 'static,
+        C: Fn(ComponentState<ST>) -> BoxFuture<'static, Markup> + Send + Sync + 'static,
+    {
+        self.try_render_cached(max_age, move |state| {
+            renderer(state).map(Ok::<_, Infallible>).boxed()
+        })
+    }
 
-            renderer: Some(Box::new(renderer)),
-            runner: self.runner,
+    /// Add a renderer that can fail.
+    ///
+    /// If the renderer returns an error, the whole page render fails and responds with the error (see [`RenderError`]).
+    /// A component that failed is not cached, so it is built again on the next render.
+    pub fn try_render<C, E>(self, renderer: C) -> impl BuildableComponent
+    where
+        ST: Clone + Send + Sync + 'static,
+        C: Fn(ComponentState<ST>) -> BoxFuture<'static, Result<Markup, E>> + Send + Sync + 'static,
+        E: IntoResponse + 'static,
+    {
+        self.with_renderer(false, renderer)
+    }
 
-            scripts: self.scripts,
-            style: self.style,
+    /// Add a renderer that can fail and is executed on every render.
+    ///
+    /// See [`try_render`](Component::try_render) for how errors are handled.
+    pub fn try_render_dynamic<C, E>(self, renderer: C) -> impl BuildableComponent
+    where
+        ST: Clone + Send + Sync + 'static,
+        C: Fn(ComponentState<ST>) -> BoxFuture<'static, Result<Markup, E>> + Send + Sync + 'static,
+        E: IntoResponse + 'static,
+    {
+        self.with_renderer(true, renderer)
+    }
 
-            _renderer_state: PhantomData,
-            _state_state: PhantomData,
-        }
+    /// Add a renderer that can fail and whose render is cached for the given duration.
+    ///
+    /// See [`render_cached`](Component::render_cached) and [`try_render`](Component::try_render).
+    pub fn try_render_cached<C, E>(self, max_age: Duration, renderer: C) -> impl BuildableComponent
+    where
+        ST: Clone + Send + Sync + 'static,
+        C: Fn(ComponentState<ST>) -> BoxFuture<'static, Result<Markup, E>> + Send + Sync + 'static,
+        E: IntoResponse + 'static,
+    {
+        let mut component = self.with_renderer(false, renderer);
+        component.max_age = Some(max_age);
+        component
     }
 
-    pub fn render_dynamic<C>(self, renderer: C) -> impl BuildableComponent
+    fn with_renderer<C, E>(self, is_dynamic: bool, renderer: C) -> Component<HasRenderer, S, ST>
     where
         ST: Clone + Send + Sync + 'static,
-        C: Fn(ComponentState<ST>) -> BoxFuture<'static, Markup> + Send + Sync + 'static,
+        C: Fn(ComponentState<ST>) -> BoxFuture<'static, Result<Markup, E>> + Send + Sync + 'static,
+        E: IntoResponse + 'static,
     {
+        let renderer = move |state| {
+            renderer(state)
+                .map(|render| render.map_err(RenderError::from_response))
+                .boxed()
+        };
+
         Component::<HasRenderer, S, ST> {
             name: self.name,
             id: self.id,
-            is_dynamic: true,
+
+            is_dynamic,
+            max_age: self.max_age,
+            sse: self.sse,
+            render_route: self.render_route,
+            lazy: self.lazy,
+            auth: self.auth,
+            wrapper: self.wrapper,
+
             state: self.state,
             router: self.router,
+
             renderer: Some(Box::new(renderer)),
             runner: self.runner,
+
             scripts: self.scripts,
             style: self.style,
+
             _renderer_state: PhantomData,
             _state_state: PhantomData,
         }
@@ -232,6 +658,12 @@ impl<R> Component<R, NoState, ()> {
             id: self.id,
 
             is_dynamic: self.is_dynamic,
+            max_age: self.max_age,
+            sse: self.sse,
+            render_route: self.render_route,
+            lazy: self.lazy,
+            auth: self.auth,
+            wrapper: self.wrapper,
 
             state,
             router: self.router,
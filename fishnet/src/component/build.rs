@@ -6,11 +6,11 @@ use std::sync::Arc;
 use tracing::{debug, instrument, trace};
 
 use super::{
-    render::{ContentType, StatefulContentRenderer},
-    Component, ComponentRoute, ComponentState, HasRenderer,
+    render::{self, ContentType, Invalidation, StatefulContentRenderer, StaticContent},
+    Component, ComponentRoute, ComponentState, HasRenderer, SseChannel, Wrapper,
 };
 use crate::css;
-use crate::page::render_context;
+use crate::page::render_context::{self, Slots};
 
 #[derive(Debug, Clone)]
 pub struct BuiltComponent {
@@ -19,8 +19,15 @@ pub struct BuiltComponent {
     id: Arc<str>,
 
     class_name: Arc<str>,
+    dom_id: Arc<str>,
+    wrapper: Wrapper,
 
     content: Arc<ContentType>,
+    // the placeholder rendered instead of the content of lazy components
+    lazy: Option<Markup>,
+    invalidation: Invalidation,
+    // the slots of the latest render, used by the render route
+    slots: Arc<parking_lot::Mutex<Slots>>,
 }
 
 pub struct ComponentBuildResult {
@@ -39,23 +46,47 @@ impl BuiltComponent {
         &self.id
     }
 
+    /// the id of the element the component is rendered into (see [`ComponentState::dom_id`]).
+    pub fn dom_id(&self) -> &str {
+        &self.dom_id
+    }
+
     pub async fn render(&self) -> Markup {
-        html! {
-            div class=(self.class_name) { (self.content.render().await) }
-        }
+        let content = match &self.lazy {
+            Some(placeholder) => placeholder.clone(),
+            None => self.content.render().await,
+        };
+        self.wrapper.wrap(&self.class_name, &self.dom_id, content)
     }
 
     pub fn render_if_static(&self) -> Option<Markup> {
-        self.content.render_if_static().map(|content| {
-            html! {
-                div class=(self.class_name) { (content) }
-            }
-        })
+        let content = match &self.lazy {
+            Some(placeholder) => Some(placeholder.clone()),
+            None => self.content.render_if_static(),
+        };
+        content.map(|content| self.wrapper.wrap(&self.class_name, &self.dom_id, content))
+    }
+
+    pub(crate) fn invalidation(&self) -> &Invalidation {
+        &self.invalidation
     }
 
+    /// remember the slots the component was rendered with, so the render route can use them.
+    pub(crate) fn update_slots(&self, slots: &Slots) {
+        let mut stored = self.slots.lock();
+        if *stored != *slots {
+            *stored = slots.clone();
+        }
+    }
+
+    /// whether the component is rendered dynamically. the placeholder of [lazy](Component::lazy) components never changes,
+    /// so they are not dynamic even if their content is.
     pub fn is_dynamic(&self) -> bool {
+        if self.lazy.is_some() {
+            return false;
+        }
         match self.content.as_ref() {
-            ContentType::Static(_) => false,
+            ContentType::Static(content) => content.is_dynamic(),
             _ => true,
         }
     }
@@ -67,6 +98,16 @@ pub trait BuildableComponent {
     fn id(&self) -> &str;
 
     async fn build(self: Self, base_route: &str) -> ComponentBuildResult;
+    /// build one of multiple instances of the component, each key gets its own api route.
+    ///
+    /// `context_id` identifies the place the component is rendered at (see [`c!`](crate::c!)),
+    /// every place (and key) gets its own [`dom_id`](super::ComponentState::dom_id).
+    async fn build_instance(
+        self,
+        base_route: &str,
+        context_id: &str,
+        key: Option<u64>,
+    ) -> ComponentBuildResult;
 }
 
 #[async_trait]
@@ -82,18 +123,74 @@ where
         &self.id
     }
 
-    #[instrument(name = "build_component", skip_all, fields(name = %self.name))]
     async fn build(self, base_route: &str) -> ComponentBuildResult {
+        let id = self.id.clone();
+        self.build_instance(base_route, &id, None).await
+    }
+
+    #[instrument(name = "build_component", skip_all, fields(name = %self.name))]
+    async fn build_instance(
+        self,
+        base_route: &str,
+        context_id: &str,
+        key: Option<u64>,
+    ) -> ComponentBuildResult {
         trace!("building state");
-        let api_route = ComponentRoute::new(base_route, &self.name, &self.id);
+        let api_route = match key {
+            Some(key) => {
+                ComponentRoute::new(base_route, &self.name, &format!("{}-{:x}", self.id, key))
+            }
+            None => ComponentRoute::new(base_route, &self.name, &self.id),
+        };
+        let dom_id: Arc<str> = match self.wrapper.id() {
+            Some(id) => id.into(),
+            None => match key {
+                Some(key) => format!("{}_{}-{:x}", self.name, context_id, key).into(),
+                None => format!("{}_{}", self.name, context_id).into(),
+            },
+        };
+        let invalidation = Invalidation::default();
+        let sse = self.sse.then(SseChannel::new);
         let state = ComponentState {
             api_route: api_route.clone(),
+            dom_id: dom_id.clone(),
+            invalidation: invalidation.clone(),
+            sse: sse.clone(),
             state: self.state,
         };
 
-        let router = self.router.map(|r| r.layer(Extension(state.clone())));
+        let mut router = self.router.map(|r| r.layer(Extension(state.clone())));
+        if let Some(sse) = &sse {
+            let sse_router = sse.router();
+            router = Some(match router {
+                Some(router) => router.merge(sse_router),
+                None => sse_router,
+            });
+            SseChannel::add_extension().await;
+        }
 
-        let renderer = self.renderer.unwrap();
+        let mut renderer = self.renderer.unwrap();
+        let mut is_dynamic = self.is_dynamic;
+        if let Some(auth) = &self.auth {
+            renderer = render::guard_renderer(auth.clone(), renderer);
+            is_dynamic = true;
+        }
+        let renderer = StatefulContentRenderer::new(renderer, state.clone());
+
+        let scope = render_context::current_scope();
+        let slots = Arc::new(parking_lot::Mutex::new(render_context::current_slots()));
+        if self.render_route {
+            let renderer = renderer.clone();
+            let slots = slots.clone();
+            let render_router = render::render_router("/render", scope.clone(), move || {
+                let slots = slots.lock().clone();
+                render_context::with_slots(slots, renderer.try_render()).boxed()
+            });
+            router = Some(match router {
+                Some(router) => router.merge(render_router),
+                None => render_router,
+            });
+        }
 
         let runner = self.runner.map(|runner| {
             let runner = (runner)(state.clone());
@@ -102,18 +199,64 @@ where
 
         let content;
 
-        if !self.is_dynamic {
+        if !is_dynamic && self.lazy.is_some() {
+            // the first render happens once the content is requested, so it doesn't hold up the page
+            content = ContentType::Static(StaticContent::new(
+                Markup::default(),
+                renderer,
+                invalidation.clone(),
+                self.max_age,
+            ));
+            invalidation.invalidate_self();
+        } else if !is_dynamic {
             trace!("pre-rendering static component");
-            render_context::enter_temporary_render().await;
-            let render = renderer(state.clone()).await;
-            if !render_context::exit_temporary_render().await {
-                debug!("detected dynamic child, making self dynamic");
-                content = ContentType::Dynamic(StatefulContentRenderer::new(renderer, state));
-            } else {
-                content = ContentType::Static(Arc::new(render));
+            render_context::enter_static_render(&invalidation).await;
+            let render = renderer.try_render().await;
+            let is_static = render_context::exit_static_render().await;
+            match render {
+                Ok(render) if is_static => {
+                    content = ContentType::Static(StaticContent::new(
+                        render,
+                        renderer,
+                        invalidation.clone(),
+                        self.max_age,
+                    ));
+                }
+                Ok(_) => {
+                    debug!("detected dynamic child, making self dynamic");
+                    content = ContentType::Dynamic(renderer);
+                }
+                Err(error) => {
+                    // failed components are not cached by the page, so it gets built again on the next render
+                    debug!("pre-render failed");
+                    render_context::fail_render(error);
+                    content = ContentType::Dynamic(renderer);
+                }
             }
         } else {
-            content = ContentType::Dynamic(StatefulContentRenderer::new(renderer, state));
+            content = ContentType::Dynamic(renderer);
+        }
+        let content = Arc::new(content);
+
+        let lazy = self.lazy.map(|lazy| {
+            let lazy_content = content.clone();
+            let lazy_router = render::render_router("/lazy", scope, move || {
+                let content = lazy_content.clone();
+                async move { Ok(content.render().await) }.boxed()
+            });
+            router = Some(match router.take() {
+                Some(router) => router.merge(lazy_router),
+                None => lazy_router,
+            });
+
+            html! {
+                div hx-get=(format!("{}/lazy", api_route.as_str())) hx-trigger=(lazy.trigger) hx-swap="outerHTML" {
+                    (lazy.placeholder)
+                }
+            }
+        });
+        if let Some(auth) = self.auth {
+            router = router.map(|router| auth.guard_router(router));
         }
 
         trace!("rendering component style");
@@ -134,7 +277,12 @@ where
                 name: self.name,
                 id: self.id,
                 class_name: class_name.into(),
-                content: Arc::new(content),
+                dom_id,
+                wrapper: self.wrapper,
+                content,
+                lazy,
+                invalidation,
+                slots,
             },
             runner,
             router: router.map(|r| (api_route, r)),
@@ -11,6 +11,15 @@ pub use crate::{component, dyn_component};
 // html, js, css
 pub use crate::js::ScriptType;
 pub use crate::{css, html, script, style, Markup};
+
+// accessing the request in dynamic components
+pub use crate::page::render_context::request;
+// markup passed into components
+pub use crate::page::render_context::{children, slot};
+
+// failing renders
+pub use crate::page::RenderError;
+pub use axum::http::StatusCode;
 pub use maud::Render;
 
 // boxing runner futures
@@ -23,4 +32,5 @@ pub use tokio::sync::Mutex;
 pub use axum::Extension;
 
 // endpoints
+pub use axum::extract::ws::{Message, WebSocket};
 pub use axum::routing;
@@ -1,11 +1,41 @@
 use super::ComponentState;
 use async_trait::async_trait;
-use futures::future::BoxFuture;
-use maud::Markup;
+use axum::{http::request::Parts, routing::get, Extension, Router};
+use futures::future::{BoxFuture, FutureExt};
+use maud::{html, Markup};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::debug;
 
-pub type ContentRenderer<ST> =
-    Box<dyn Fn(ComponentState<ST>) -> BoxFuture<'static, Markup> + Send + Sync>;
+use crate::auth::Auth;
+use crate::page::render_context::{self, ComponentStore};
+use crate::page::{BuiltPage, PathParams, RenderError, RequestContext};
+
+pub type ContentRenderer<ST> = Box<
+    dyn Fn(ComponentState<ST>) -> BoxFuture<'static, Result<Markup, RenderError>> + Send + Sync,
+>;
+
+/// only call the renderer if the request of the current render is authenticated, rendering the fallback otherwise.
+pub(crate) fn guard_renderer<ST>(auth: Auth, renderer: ContentRenderer<ST>) -> ContentRenderer<ST>
+where
+    ST: Clone + Send + Sync + 'static,
+{
+    let renderer = Arc::new(renderer);
+    Box::new(move |state| {
+        let auth = auth.clone();
+        let renderer = renderer.clone();
+        async move {
+            if auth.authenticate_render().await {
+                renderer(state).await
+            } else {
+                Ok(auth.fallback_markup())
+            }
+        }
+        .boxed()
+    })
+}
 
 pub struct StatefulContentRenderer<ST>
 where
@@ -21,6 +51,63 @@ where
     pub fn new(renderer: ContentRenderer<ST>, state: ComponentState<ST>) -> Arc<Self> {
         Arc::new(Self { renderer, state })
     }
+
+    pub fn try_render(&self) -> BoxFuture<'static, Result<Markup, RenderError>> {
+        (self.renderer)(self.state.clone())
+    }
+}
+
+/// a router responding with the markup returned by `render` at `path`, used for the [render route](super::Component::render_route)
+/// and [lazy](super::Component::lazy) components.
+///
+/// `scope` is the route and component store the component was built with, so its children are looked up in there.
+/// the render gets the parameters of the page the request was sent from (see [`BuiltPage::request_params`]).
+pub(crate) fn render_router<F>(
+    path: &str,
+    scope: Option<(String, Arc<parking_lot::Mutex<ComponentStore>>)>,
+    render: F,
+) -> Router
+where
+    F: Fn() -> BoxFuture<'static, Result<Markup, RenderError>> + Clone + Send + Sync + 'static,
+{
+    let (base_route, components) = scope.unwrap_or_default();
+    Router::new().route(
+        path,
+        get(
+            move |page: Option<Extension<Arc<BuiltPage>>>,
+                  params: Option<Extension<PathParams>>,
+                  parts: Parts| {
+                let (base_route, components) = (base_route.clone(), components.clone());
+                let render = render.clone();
+                async move {
+                    let params = params.map(|Extension(params)| params).unwrap_or_default();
+                    let params = match &page {
+                        Some(Extension(page)) => page.request_params(params, &parts.headers),
+                        None => params,
+                    };
+                    let request = RequestContext::new(parts, params);
+                    let (render, result) = render_context::render_page(
+                        &base_route,
+                        components,
+                        Some(request),
+                        render(),
+                    )
+                    .await;
+
+                    match page {
+                        Some(Extension(page)) => page.apply_render_result(result).await,
+                        // components within layouts are served without a page, their existing children are still found in the store
+                        None if !result.routers.is_empty() || !result.runners.is_empty() => {
+                            debug!("discarding components that were newly built by a render route")
+                        }
+                        None => {}
+                    }
+
+                    render
+                }
+            },
+        ),
+    )
 }
 
 #[async_trait]
@@ -34,26 +121,196 @@ where
     ST: Clone + Send + Sync,
 {
     async fn render(&self) -> Markup {
-        (self.renderer)(self.state.clone()).await
+        match self.try_render().await {
+            Ok(render) => render,
+            Err(error) => {
+                render_context::fail_render(error);
+                html! {}
+            }
+        }
+    }
+}
+
+/// marks the cached render of a component as outdated (see [`ComponentState::invalidate`]).
+///
+/// every static component containing the component is linked as a parent, since their cached render contains its markup.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Invalidation(Arc<InvalidationInner>);
+
+#[derive(Debug, Default)]
+struct InvalidationInner {
+    stale: AtomicBool,
+    // the render of cached components (and their static parents) becomes stale by itself after some time
+    expires: parking_lot::Mutex<Option<Instant>>,
+    parents: parking_lot::Mutex<Vec<Invalidation>>,
+}
+
+impl Invalidation {
+    pub fn invalidate(&self) {
+        self.0.stale.store(true, Ordering::Release);
+        for parent in self.0.parents.lock().iter() {
+            parent.invalidate();
+        }
+    }
+
+    /// mark only this render as stale, without invalidating the parents.
+    pub fn invalidate_self(&self) {
+        self.0.stale.store(true, Ordering::Release);
+    }
+
+    pub fn add_parent(&self, parent: &Invalidation) {
+        if Arc::ptr_eq(&self.0, &parent.0) {
+            return;
+        }
+
+        let mut parents = self.0.parents.lock();
+        if !parents.iter().any(|known| Arc::ptr_eq(&known.0, &parent.0)) {
+            parents.push(parent.clone());
+        }
+        drop(parents);
+
+        if let Some(expires) = *self.0.expires.lock() {
+            parent.expire_at(expires);
+        }
+    }
+
+    /// make the render stale at the given time, unless it already becomes stale earlier.
+    fn expire_at(&self, at: Instant) {
+        let mut expires = self.0.expires.lock();
+        *expires = Some(expires.map_or(at, |expires| expires.min(at)));
+    }
+
+    fn is_expired(expires: Option<Instant>) -> bool {
+        expires.is_some_and(|expires| expires <= Instant::now())
+    }
+
+    fn is_stale(&self) -> bool {
+        self.0.stale.load(Ordering::Acquire) || Self::is_expired(*self.0.expires.lock())
+    }
+
+    // the expiry is reset as well, the children rendered afterwards set it again
+    fn take_stale(&self) -> bool {
+        let stale = self.0.stale.swap(false, Ordering::AcqRel);
+        let mut expires = self.0.expires.lock();
+        if stale || Self::is_expired(*expires) {
+            *expires = None;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn restore_stale(&self) {
+        self.0.stale.store(true, Ordering::Release);
+    }
+}
+
+/// the cached render of a static component, rendered again once it is invalidated or too old.
+pub struct StaticContent {
+    // the render and the hash of the slots it was rendered with.
+    // components with slots are rendered again whenever they get different ones, e.g. from another request.
+    render: parking_lot::RwLock<(u64, Arc<Markup>)>,
+    renderer: Arc<dyn StatefulRenderer>,
+    invalidation: Invalidation,
+    max_age: Option<Duration>,
+    // set once a render contained dynamic children, the content isn't cached anymore afterwards
+    dynamic: AtomicBool,
+}
+impl StaticContent {
+    pub(crate) fn new(
+        render: Markup,
+        renderer: Arc<dyn StatefulRenderer>,
+        invalidation: Invalidation,
+        max_age: Option<Duration>,
+    ) -> Self {
+        let content = Self {
+            render: parking_lot::RwLock::new((render_context::slots_hash(), Arc::new(render))),
+            renderer,
+            invalidation,
+            max_age,
+            dynamic: AtomicBool::new(false),
+        };
+        content.update_expiry();
+        content
+    }
+
+    fn update_expiry(&self) {
+        if let Some(max_age) = self.max_age {
+            self.invalidation.expire_at(Instant::now() + max_age);
+        }
+    }
+
+    /// whether the content gained dynamic children since it was built.
+    pub(crate) fn is_dynamic(&self) -> bool {
+        self.dynamic.load(Ordering::Acquire)
+    }
+
+    fn cached(&self) -> Option<Markup> {
+        if self.is_dynamic() || self.invalidation.is_stale() {
+            return None;
+        }
+        let (slots, render) = &*self.render.read();
+        (*slots == render_context::slots_hash()).then(|| render.as_ref().clone())
+    }
+
+    async fn render(&self) -> Markup {
+        if self.is_dynamic() {
+            return self.renderer.render().await;
+        }
+
+        let slots = render_context::slots_hash();
+        if !self.invalidation.take_stale() {
+            let (cached_slots, render) = &*self.render.read();
+            if *cached_slots == slots {
+                return render.as_ref().clone();
+            }
+            debug!("slots changed, rendering component again");
+        } else {
+            debug!("rendering stale component");
+        }
+
+        let errors = render_context::error_count();
+        render_context::enter_static_render(&self.invalidation).await;
+        let render = self.renderer.render().await;
+        let is_static = render_context::exit_static_render().await;
+
+        if render_context::error_count() > errors {
+            // keep it stale, so the next render can try again
+            self.invalidation.restore_stale();
+            render
+        } else if !is_static {
+            // the component gained dynamic children, it can't be cached anymore
+            debug!("invalidated component is not static anymore, making it dynamic");
+            self.dynamic.store(true, Ordering::Release);
+            // the static parents contain the cached render as well, they find out about the dynamic child when rendering again
+            self.invalidation.invalidate();
+            // dynamic children are left out of static renders, so the render has to be repeated once
+            self.renderer.render().await
+        } else {
+            *self.render.write() = (slots, Arc::new(render.clone()));
+            self.update_expiry();
+            render
+        }
     }
 }
 
 pub enum ContentType {
     Dynamic(Arc<dyn StatefulRenderer>),
-    Static(Arc<Markup>),
+    Static(StaticContent),
 }
 impl ContentType {
     pub async fn render(&self) -> Markup {
         match self {
             ContentType::Dynamic(renderer) => renderer.render().await,
-            ContentType::Static(content) => content.as_ref().clone(),
+            ContentType::Static(content) => content.render().await,
         }
     }
 
+    /// the cached render of a static component, if it is still up to date.
     #[inline]
     pub fn render_if_static(&self) -> Option<Markup> {
         match self {
-            ContentType::Static(content) => Some(content.as_ref().clone()),
+            ContentType::Static(content) => content.cached(),
             _ => None,
         }
     }
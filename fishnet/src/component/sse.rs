@@ -0,0 +1,87 @@
+//! server-sent events for pushing updates from a component to everyone viewing it.
+
+use axum::{
+    response::sse::{Event, KeepAlive, Sse},
+    routing::get,
+    Extension, Router,
+};
+use core::convert::Infallible;
+use futures::stream::{self, Stream};
+use maud::Markup;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::{debug, trace};
+
+use crate::js::ScriptType;
+use crate::page::render_context::{self, GlobalStoreEntry};
+
+// clients that fall behind by more than this many events skip the oldest ones
+const CHANNEL_CAPACITY: usize = 32;
+
+#[derive(Debug, Clone)]
+struct SseMessage {
+    event: Option<String>,
+    data: String,
+}
+
+/// the channel of a component with an sse stream, shared by all its clients.
+#[derive(Debug, Clone)]
+pub(crate) struct SseChannel(broadcast::Sender<SseMessage>);
+
+impl SseChannel {
+    pub fn new() -> Self {
+        Self(broadcast::channel(CHANNEL_CAPACITY).0)
+    }
+
+    /// send a message to all connected clients, returns the number of clients it was sent to.
+    pub fn send(&self, event: Option<&str>, markup: Markup) -> usize {
+        let message = SseMessage {
+            event: event.map(str::to_string),
+            data: markup.into_string(),
+        };
+
+        // sending only fails if no client is connected
+        self.0.send(message).unwrap_or(0)
+    }
+
+    /// the router serving the event stream of the component.
+    pub fn router(&self) -> Router {
+        Router::new().route("/sse", get(subscribe).layer(Extension(self.clone())))
+    }
+
+    /// add the htmx sse extension to the page that is currently being rendered.
+    pub async fn add_extension() {
+        render_context::global_store()
+            .add("fishnet-htmx-sse", || GlobalStoreEntry {
+                scripts: vec![ScriptType::Inline(include_str!(
+                    "../../htmx/dist/ext/sse.js"
+                ))],
+                style: None,
+            })
+            .await;
+    }
+}
+
+async fn subscribe(
+    Extension(channel): Extension<SseChannel>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    trace!("client subscribed to sse stream");
+    let events = stream::unfold(channel.0.subscribe(), |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(message) => {
+                    let mut event = Event::default().data(message.data);
+                    if let Some(name) = message.event {
+                        event = event.event(name);
+                    }
+                    return Some((Ok(event), receiver));
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    debug!(skipped, "sse client fell behind, skipping events");
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
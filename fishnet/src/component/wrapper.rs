@@ -0,0 +1,231 @@
+use maud::{Escaper, Markup, PreEscaped};
+use std::fmt::Write;
+use std::sync::Arc;
+
+// elements that can't have any content, so they can't wrap a component either
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// why `tag` can't be used as the wrapper of a component, if it can't.
+///
+/// this is `const`, so the `component` macro checks literal tags at compile time using the same rules.
+#[doc(hidden)]
+pub const fn tag_error(tag: &str) -> Option<&'static str> {
+    let tag = tag.as_bytes();
+    if tag.is_empty() || !tag[0].is_ascii_alphabetic() {
+        return Some("not a valid element name");
+    }
+    let mut i = 0;
+    while i < tag.len() {
+        if !(tag[i].is_ascii_alphanumeric() || tag[i] == b'-') {
+            return Some("not a valid element name");
+        }
+        i += 1;
+    }
+
+    let mut i = 0;
+    while i < VOID_ELEMENTS.len() {
+        if VOID_ELEMENTS[i].as_bytes().eq_ignore_ascii_case(tag) {
+            return Some("void elements can't have any content, so they can't wrap a component");
+        }
+        i += 1;
+    }
+    None
+}
+
+/// why `name` can't be used as an attribute of a component wrapper, if it can't (see [`tag_error`]).
+#[doc(hidden)]
+pub const fn attr_error(name: &str) -> Option<&'static str> {
+    let name = name.as_bytes();
+    if name.is_empty() {
+        return Some("not a valid attribute name");
+    }
+    let mut i = 0;
+    while i < name.len() {
+        let c = name[i];
+        if !(c.is_ascii_alphanumeric() || matches!(c, b'-' | b'_' | b':' | b'.' | b'@')) {
+            return Some("not a valid attribute name");
+        }
+        i += 1;
+    }
+
+    if name.eq_ignore_ascii_case(b"class") {
+        return Some("use `class` for adding classes");
+    }
+    None
+}
+
+/// the element a component is rendered into (see [`Component::tag`](super::Component::tag)).
+#[derive(Debug, Clone)]
+pub(crate) struct Wrapper {
+    // `None` renders the component without a wrapper
+    tag: Option<Arc<str>>,
+    classes: Vec<String>,
+    attrs: Vec<(String, String)>,
+}
+
+impl Default for Wrapper {
+    fn default() -> Self {
+        Self {
+            tag: Some(Arc::from("div")),
+            classes: Vec::new(),
+            attrs: Vec::new(),
+        }
+    }
+}
+
+impl Wrapper {
+    pub fn set_tag(&mut self, tag: &str) {
+        if let Some(error) = tag_error(tag) {
+            panic!(
+                "'{}' can't be used as the wrapper of a component: {}",
+                tag, error
+            );
+        }
+        self.tag = Some(Arc::from(tag));
+    }
+
+    pub fn set_fragment(&mut self) {
+        self.tag = None;
+    }
+
+    pub fn add_class(&mut self, class: &str) {
+        self.classes.push(class.to_string());
+    }
+
+    /// set an attribute, replacing any previous value.
+    pub fn set_attr(&mut self, name: &str, value: String) {
+        if let Some(error) = attr_error(name) {
+            panic!(
+                "'{}' can't be used as an attribute of a component wrapper: {}",
+                name, error
+            );
+        }
+
+        // attribute names are case-insensitive, so e.g. `ID` replaces the id as well
+        match self
+            .attrs
+            .iter_mut()
+            .find(|(attr, _)| attr.eq_ignore_ascii_case(name))
+        {
+            Some((_, old)) => *old = value,
+            None => self.attrs.push((name.to_string(), value)),
+        }
+    }
+
+    /// the id set using [`set_attr`](Self::set_attr), if any.
+    pub fn id(&self) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(attr, _)| attr.eq_ignore_ascii_case("id"))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// wrap the rendered content of a component, `class_name` is the class the styles of the component are scoped to.
+    ///
+    /// `dom_id` is used as the id of the element, unless a different one was set.
+    pub fn wrap(&self, class_name: &str, dom_id: &str, content: Markup) -> Markup {
+        let Some(tag) = &self.tag else {
+            return content;
+        };
+
+        let mut html = String::with_capacity(content.0.len() + 64);
+        let _ = write!(html, "<{} class=\"", tag);
+        let _ = Escaper::new(&mut html).write_str(class_name);
+        for class in &self.classes {
+            html.push(' ');
+            let _ = Escaper::new(&mut html).write_str(class);
+        }
+        html.push('"');
+        if self.id().is_none() {
+            html.push_str(" id=\"");
+            let _ = Escaper::new(&mut html).write_str(dom_id);
+            html.push('"');
+        }
+        for (name, value) in &self.attrs {
+            let _ = write!(html, " {}=\"", name);
+            let _ = Escaper::new(&mut html).write_str(value);
+            html.push('"');
+        }
+        html.push('>');
+        html.push_str(&content.0);
+        let _ = write!(html, "</{}>", tag);
+
+        PreEscaped(html)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use maud::html;
+
+    #[test]
+    fn test_wrap() {
+        let content = html! { "content" };
+
+        let wrapper = Wrapper::default();
+        assert_eq!(
+            wrapper
+                .wrap("my-nav", "nav-1", content.clone())
+                .into_string(),
+            "<div class=\"my-nav\" id=\"nav-1\">content</div>"
+        );
+
+        let mut wrapper = Wrapper::default();
+        wrapper.set_tag("nav");
+        wrapper.add_class("main");
+        wrapper.set_attr("aria-label", "\"main\" navigation".to_string());
+        assert_eq!(
+            wrapper.wrap("my-nav", "nav-1", content.clone()).into_string(),
+            "<nav class=\"my-nav main\" id=\"nav-1\" aria-label=\"&quot;main&quot; navigation\">content</nav>"
+        );
+
+        wrapper.set_attr("id", "main-nav".to_string());
+        assert_eq!(
+            wrapper.wrap("my-nav", "nav-1", content.clone()).into_string(),
+            "<nav class=\"my-nav main\" aria-label=\"&quot;main&quot; navigation\" id=\"main-nav\">content</nav>"
+        );
+
+        wrapper.set_fragment();
+        assert_eq!(
+            wrapper.wrap("my-nav", "nav-1", content).into_string(),
+            "content"
+        );
+    }
+
+    #[test]
+    fn test_attr_case() {
+        let mut wrapper = Wrapper::default();
+        wrapper.set_attr("ID", "main-nav".to_string());
+        wrapper.set_attr("Aria-Label", "main".to_string());
+        wrapper.set_attr("aria-label", "navigation".to_string());
+        assert_eq!(
+            wrapper
+                .wrap("my-nav", "nav-1", html! { "content" })
+                .into_string(),
+            "<div class=\"my-nav\" ID=\"main-nav\" Aria-Label=\"navigation\">content</div>"
+        );
+    }
+
+    #[test]
+    fn test_validation() {
+        assert_eq!(tag_error("nav"), None);
+        assert_eq!(tag_error("my-element"), None);
+        assert!(tag_error("IMG").is_some());
+        assert!(tag_error("1div").is_some());
+        assert!(tag_error("").is_some());
+
+        assert_eq!(attr_error("hx-on:click"), None);
+        assert!(attr_error("Class").is_some());
+        assert!(attr_error("on click").is_some());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_void_tag() {
+        Wrapper::default().set_tag("img");
+    }
+}
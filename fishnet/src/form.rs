@@ -0,0 +1,405 @@
+//! typed forms with validation, meant for handling form submissions in component routes.
+//!
+//! a form is a struct deriving [`Form`](macro@Form). the derive describes the fields of the struct, which is used for
+//! rendering the form and for parsing and validating submitted values. submissions are extracted using [`ValidForm`]. if the
+//! submitted values are invalid, the extractor responds with the form again, this time containing the submitted values and an
+//! error message below every invalid field. since the form is swapped with the response, htmx shows the errors right away.
+//!
+//! ```rust
+//! use fishnet::component::prelude::*;
+//! use fishnet::form::{Form, ValidForm};
+//!
+//! #[derive(Form)]
+//! #[form(submit = "sign up")]
+//! struct Signup {
+//!     #[form(label = "user name", min_length = 3, max_length = 20)]
+//!     name: String,
+//!     #[form(input = "email")]
+//!     email: String,
+//!     #[form(label = "age (optional)")]
+//!     age: Option<u8>,
+//!     newsletter: bool,
+//! }
+//!
+//! #[component]
+//! async fn signup_form() {
+//!     let state = state!(());
+//!
+//!     #[route("/", POST)]
+//!     async fn submit(ValidForm(signup): ValidForm<Signup>) -> Markup {
+//!         html! { "welcome, " (signup.name) "!" }
+//!     }
+//!
+//!     html! {
+//!         (Signup::render_form(state.endpoint(), state.dom_id()))
+//!     }
+//! }
+//! ```
+//!
+//! # field attributes
+//! - `label = "..."` - the label shown in front of the input, defaults to the name of the field.
+//! - `input = "..."` - the type of the input (e.g. `email`, `password` or `textarea`), defaults to a type fitting the field type.
+//! - `min_length = n` / `max_length = n` - limit the length of the value.
+//! - `validate = path::to::function` - a custom validation function taking the parsed value and returning a `Result<(), String>`.
+//!
+//! fields are required unless their type is an [`Option`] or a `bool` (which is rendered as a checkbox).
+
+use axum::{
+    async_trait,
+    body::Bytes,
+    extract::{FromRequest, OriginalUri, Request},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use hashbrown::HashMap;
+use maud::{html, Markup};
+use std::str::FromStr;
+
+/// derive macro for [`Form`](trait@Form), see the [module level documentation](self).
+pub use fishnet_macros::Form;
+
+/// a struct that can be rendered as a html form and parsed from its submissions.
+///
+/// you usually want to [derive](macro@Form) this instead of implementing it manually.
+pub trait Form: Sized {
+    /// the name of the form, part of the ids of the inputs.
+    const NAME: &'static str;
+    /// the label of the submit button.
+    const SUBMIT: &'static str = "submit";
+
+    /// the fields of the form.
+    fn fields() -> Vec<FormFieldInfo>;
+
+    /// parse and validate the submitted values.
+    fn parse(values: &FormValues) -> Result<Self, FormErrors>;
+
+    /// render the empty form, submitting to the given url (usually the [`endpoint`](crate::component::ComponentState::endpoint) of a component).
+    ///
+    /// `id` prefixes the ids of the inputs, so they stay unique when the form is rendered more than once on a page.
+    /// within a component this is usually its [`dom_id`](crate::component::ComponentState::dom_id).
+    fn render_form(action: &str, id: &str) -> Markup {
+        render_form::<Self>(action, id, &FormValues::default(), &FormErrors::default())
+    }
+}
+
+/// the description of a single field of a [`Form`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormFieldInfo {
+    name: &'static str,
+    label: &'static str,
+    input: &'static str,
+    required: bool,
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+}
+
+impl FormFieldInfo {
+    /// create a new field with the given name, using the name as label.
+    pub fn new(name: &'static str, input: &'static str, required: bool) -> Self {
+        Self {
+            name,
+            label: name,
+            input,
+            required,
+            min_length: None,
+            max_length: None,
+        }
+    }
+
+    /// set the label shown in front of the input.
+    pub fn label(mut self, label: &'static str) -> Self {
+        self.label = label;
+        self
+    }
+
+    /// set the minimum length of the value.
+    pub fn min_length(mut self, min_length: usize) -> Self {
+        self.min_length = Some(min_length);
+        self
+    }
+
+    /// set the maximum length of the value.
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    /// the name of the field.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// parse the value of this field from the submitted values, checking the length limits.
+    ///
+    /// if the value is invalid, the error is added to `errors` and `None` is returned.
+    pub fn parse<T: FormField>(&self, values: &FormValues, errors: &mut FormErrors) -> Option<T> {
+        let value = values.get(self.name).filter(|value| !value.is_empty());
+
+        if let Some(value) = value {
+            let length = value.chars().count();
+            if self.min_length.is_some_and(|min| length < min) {
+                errors.add(
+                    self.name,
+                    format!(
+                        "must be at least {} characters long",
+                        self.min_length.unwrap()
+                    ),
+                );
+                return None;
+            }
+            if self.max_length.is_some_and(|max| length > max) {
+                errors.add(
+                    self.name,
+                    format!(
+                        "must be at most {} characters long",
+                        self.max_length.unwrap()
+                    ),
+                );
+                return None;
+            }
+        }
+
+        match T::parse(value) {
+            Ok(value) => Some(value),
+            Err(error) => {
+                errors.add(self.name, error);
+                None
+            }
+        }
+    }
+}
+
+/// a type that can be used as the type of a field in a [`Form`].
+pub trait FormField: Sized {
+    /// the type of the input used for the field, if not set otherwise.
+    const INPUT: &'static str = "text";
+    /// whether the field has to be filled in.
+    const REQUIRED: bool = true;
+
+    /// parse the submitted value, `None` if the field was left empty.
+    fn parse(value: Option<&str>) -> Result<Self, String>;
+}
+
+impl FormField for String {
+    fn parse(value: Option<&str>) -> Result<Self, String> {
+        value
+            .map(str::to_string)
+            .ok_or_else(|| "this field is required".to_string())
+    }
+}
+
+impl FormField for bool {
+    const INPUT: &'static str = "checkbox";
+    const REQUIRED: bool = false;
+
+    fn parse(value: Option<&str>) -> Result<Self, String> {
+        Ok(matches!(value, Some("true" | "on")))
+    }
+}
+
+impl<T: FormField> FormField for Option<T> {
+    const INPUT: &'static str = T::INPUT;
+    const REQUIRED: bool = false;
+
+    fn parse(value: Option<&str>) -> Result<Self, String> {
+        match value {
+            Some(value) => T::parse(Some(value)).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+macro_rules! number_field {
+    ($($number:ty),*) => {$(
+        impl FormField for $number {
+            const INPUT: &'static str = "number";
+
+            fn parse(value: Option<&str>) -> Result<Self, String> {
+                let value = value.ok_or_else(|| "this field is required".to_string())?;
+                <$number>::from_str(value.trim()).map_err(|_| "must be a valid number".to_string())
+            }
+        }
+    )*};
+}
+number_field!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, f32, f64);
+
+/// the raw values of a form submission.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FormValues(HashMap<String, String>);
+
+impl FormValues {
+    /// parse url encoded form data (`application/x-www-form-urlencoded`).
+    pub fn from_urlencoded(data: &[u8]) -> Self {
+        Self(form_urlencoded::parse(data).into_owned().collect())
+    }
+
+    /// the submitted value of a field.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0.get(name).map(String::as_str)
+    }
+
+    /// set the value of a field.
+    pub fn insert(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        self.0.insert(name.into(), value.into());
+    }
+}
+
+/// the validation errors of a form submission, by field.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FormErrors(HashMap<String, String>);
+
+impl FormErrors {
+    /// create a new empty set of errors.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// add an error to a field. only the first error of every field is kept.
+    pub fn add(&mut self, field: &str, error: impl Into<String>) {
+        self.0
+            .entry(field.to_string())
+            .or_insert_with(|| error.into());
+    }
+
+    /// the error of a field.
+    pub fn get(&self, field: &str) -> Option<&str> {
+        self.0.get(field).map(String::as_str)
+    }
+
+    /// whether there are no errors.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+// the hidden field sending the id prefix along, so a rejected submission is rendered with the same ids again
+const FORM_ID_FIELD: &str = "fishnet-form-id";
+
+/// render a form with the given values and errors, `id` prefixes the ids of the inputs (see [`Form::render_form`]).
+///
+/// the form submits using htmx and gets replaced by the response, so rejected submissions show their errors in place.
+pub fn render_form<F: Form>(
+    action: &str,
+    id: &str,
+    values: &FormValues,
+    errors: &FormErrors,
+) -> Markup {
+    let prefix = format!("{}-{}", id, F::NAME);
+    html! {
+        form class="fishnet-form" action=(action) method="post" hx-post=(action) hx-swap="outerHTML" {
+            input type="hidden" name=(FORM_ID_FIELD) value=(id);
+            @for field in F::fields() {
+                (render_field(&prefix, &field, values.get(field.name), errors.get(field.name)))
+            }
+            button type="submit" { (F::SUBMIT) }
+        }
+    }
+}
+
+fn render_field(
+    prefix: &str,
+    field: &FormFieldInfo,
+    value: Option<&str>,
+    error: Option<&str>,
+) -> Markup {
+    let id = format!("{}-{}", prefix, field.name);
+    let error_id = format!("{}-error", id);
+
+    let input = match field.input {
+        "textarea" => html! {
+            textarea id=(id) name=(field.name) required[field.required]
+                minlength=[field.min_length] maxlength=[field.max_length]
+                aria-invalid=[error.map(|_| "true")] aria-describedby=[error.map(|_| &error_id)] {
+                (value.unwrap_or_default())
+            }
+        },
+        "checkbox" => html! {
+            input id=(id) name=(field.name) type="checkbox" value="true"
+                checked[matches!(value, Some("true" | "on"))]
+                aria-invalid=[error.map(|_| "true")] aria-describedby=[error.map(|_| &error_id)];
+        },
+        input => html! {
+            // passwords are never sent back to the client
+            input id=(id) name=(field.name) type=(input)
+                value=[value.filter(|_| input != "password")] required[field.required]
+                minlength=[field.min_length] maxlength=[field.max_length]
+                aria-invalid=[error.map(|_| "true")] aria-describedby=[error.map(|_| &error_id)];
+        },
+    };
+
+    html! {
+        div class="fishnet-field" {
+            label for=(id) { (field.label) }
+            (input)
+            @if let Some(error) = error {
+                p id=(error_id) class="fishnet-field-error" { (error) }
+            }
+        }
+    }
+}
+
+/// extractor for a valid submission of a [`Form`].
+///
+/// invalid submissions are rejected with the form containing the submitted values and error messages. the rejection uses the
+/// `200 OK` status, since htmx doesn't swap in responses with error statuses.
+pub struct ValidForm<F>(pub F);
+
+#[async_trait]
+impl<F, S> FromRequest<S> for ValidForm<F>
+where
+    F: Form,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        // component routes only see the part of the path after the component, the form has to be sent to the full path
+        let action = match req.extensions().get::<OriginalUri>() {
+            Some(OriginalUri(uri)) => uri.path().to_string(),
+            None => req.uri().path().to_string(),
+        };
+
+        let body = Bytes::from_request(req, state)
+            .await
+            .map_err(|_| (StatusCode::BAD_REQUEST, "invalid form body").into_response())?;
+        let values = FormValues::from_urlencoded(&body);
+
+        match F::parse(&values) {
+            Ok(form) => Ok(Self(form)),
+            Err(errors) => {
+                let id = values.get(FORM_ID_FIELD).unwrap_or_default();
+                Err(render_form::<F>(&action, id, &values, &errors).into_response())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_parse() {
+        let mut values = FormValues::default();
+        values.insert("name", "fi");
+        values.insert("count", "twelve");
+        values.insert("empty", "");
+
+        let mut errors = FormErrors::new();
+        let name = FormFieldInfo::new("name", "text", true).min_length(3);
+        assert_eq!(name.parse::<String>(&values, &mut errors), None);
+        let count = FormFieldInfo::new("count", "number", true);
+        assert_eq!(count.parse::<u32>(&values, &mut errors), None);
+        let empty = FormFieldInfo::new("empty", "number", false);
+        assert_eq!(empty.parse::<Option<u32>>(&values, &mut errors), Some(None));
+        let missing = FormFieldInfo::new("missing", "checkbox", false);
+        assert_eq!(missing.parse::<bool>(&values, &mut errors), Some(false));
+
+        assert_eq!(
+            errors.get("name"),
+            Some("must be at least 3 characters long")
+        );
+        assert_eq!(errors.get("count"), Some("must be a valid number"));
+        assert_eq!(errors.get("empty"), None);
+    }
+}
@@ -33,7 +33,7 @@
 //!         .await;
 //!
 //!    // serve the website on port 8080
-//!     website.serve(8080).await;
+//!     website.serve(8080).await.unwrap();
 //! }
 //! ```
 //!
@@ -96,6 +96,10 @@
 //! in this scenario, the `my_awesome_button` function and the components render function are both run exactly once over the lifetime of the whole page, even if the page is visited multiple times.
 //! (this may not always be the case, see [the section on dynamic components](#dynamic-components) for more info.)
 //!
+//! to wrap content in a component (e.g. a card or a collapsible section), pass it in as markup after the component:
+//! `c!(card(), html! { "content" })`. the component renders it using [`children`](crate::page::render_context::children),
+//! see [slots](crate::c!#slots) for more.
+//!
 //! ### components vs html! in functions (or: why do i need to use a component at all?)
 //! you might be wondering why using components is better than just something like this:
 //! ```rust
@@ -151,6 +155,14 @@
 //! but it should be taken into consideration nonetheless. this is also why **you should never rely
 //! on your static components render function being called only once**.
 //!
+//! if your content only changes every now and then, you can instead [cache](macro@component#cached-components)
+//! a component for a fixed amount of time, or [invalidate](crate::component::ComponentState::invalidate) it whenever it changes.
+//!
+//! dynamic components (and page bodies) can also access the request they are being rendered for
+//! using [`request`](crate::page::render_context::request), e.g. for reading query parameters,
+//! cookies or the preferred language of the visitor. if [sessions](crate::Website::sessions) are enabled, this also includes
+//! the [`Session`](crate::session::Session) of the visitor, so e.g. the visit counter above could tell repeat visitors apart.
+//!
 //! ## htmx
 //! fishnet is built around supporting [htmx](https://htmx.org/). each component automagically gets
 //! assigned its very own api endpoint. you can add routes to it using the [`route`](crate::component::Component::route) function
@@ -182,9 +194,19 @@
 //! as you can see, the components state also gets passed to the components routes as an axum
 //! [`Extension`](https://docs.rs/axum/latest/axum/struct.Extension.html)
 //!
+//! each component is rendered into an element with its own [`dom_id`](crate::component::ComponentState::dom_id). for updating
+//! the whole component from one of its routes, use [`target`](crate::component::ComponentState::target) as the `hx-target`
+//! or respond with [`swap_self`](crate::component::ComponentState::swap_self).
+//!
 //! of course these api endpoints are not restricted to being used with htmx. you can serve
 //! anything that can be made into an axum response!
 //!
+//! for handling form submissions with validation, take a look at the [`form`](crate::form) module. per-visitor state like a theme choice can be
+//! kept in a [`Session`](crate::session::Session), which routes can extract just like the components state.
+//!
+//! for pushing updates to the page, components can also have [websocket routes](crate::component::Component::websocket)
+//! (using `#[websocket("/path")]` instead of `#[route(..)]`) and [server-sent event streams](crate::component::Component::sse_stream).
+//!
 //! ## styling
 //! fishnet provides its own [`css!`](crate::css!) macro that you can use to style your components.
 //! it supports a slightly modified version of the normal css syntax that applies styles relative
@@ -247,11 +269,16 @@ mod routes;
 pub mod page;
 pub use page::Page;
 
-mod website;
+pub mod website;
 pub use website::Website;
 
+pub mod auth;
 pub mod css;
+pub mod form;
 pub mod js;
+pub mod session;
+
+pub mod testing;
 
 /// macro for generating a [`StyleFragment`](crate::css::StyleFragment) from css.
 ///
@@ -345,11 +372,96 @@ pub use fishnet_macros::css;
 ///         "hello world!"
 ///     }
 /// }
-///```
+/// ```
+///
+/// ## failing components
+/// if the function returns a `Result<Markup, E>` (where `E` implements [`IntoResponse`](axum::response::IntoResponse)),
+/// the page render fails whenever the component returns an error and the page responds with the error instead
+/// (see [`RenderError`](page::RenderError)). failed components are not cached, so they are built again on the next render.
+///
+/// ```rust
+/// use fishnet::component::prelude::*;
+///
+/// #[component]
+/// async fn post_count() -> Result<Markup, RenderError> {
+///     let count: usize = std::fs::read_to_string("post_count.txt")
+///         .map_err(|_| RenderError::not_found())?
+///         .trim()
+///         .parse()
+///         .map_err(|_| RenderError::new(StatusCode::INTERNAL_SERVER_ERROR, "invalid post count"))?;
+///
+///     Ok(html! {
+///         (count) " posts"
+///     })
+/// }
+/// ```
+///
+/// ## cached components
+/// components are rendered once and cached afterwards. by passing a duration to the macro, the cache expires after
+/// that amount of time and the component is rendered again on the next page render
+/// (see [`render_cached`](component::Component::render_cached)).
+/// unlike [dynamic components](macro@dyn_component), this doesn't force the parent components to be rendered on every visit.
+/// the duration can be given in milliseconds (`ms`), seconds (`s`), minutes (`m`) or hours (`h`).
+///
+/// ```rust
+/// use fishnet::component::prelude::*;
+///
+/// #[component(cache = "60s")]
+/// async fn server_time() {
+///     html! {
+///         "last updated: " (format!("{:?}", std::time::SystemTime::now()))
+///     }
+/// }
+/// ```
+///
+/// ## server-sent events
+/// the `sse` flag adds a server-sent event stream to the component, which can be used to push updates to everyone viewing it
+/// (see [`sse_stream`](component::Component::sse_stream)). flags and arguments can be combined, e.g. `#[component(sse, cache = "5m")]`.
+///
+/// ## render route
+/// the `render_route` flag adds a route responding with the current content of the component, which can be used with `hx-get` to
+/// refresh it (see [`render_route`](component::Component::render_route)).
+///
+/// ## lazy components
+/// the `lazy` flag renders a placeholder instead of the component, the content is loaded by htmx after the page has loaded
+/// (see [`lazy`](component::Component::lazy)). `lazy = "revealed"` waits until the placeholder is scrolled into view and
+/// `placeholder = ...` takes the markup shown until then.
+///
+/// ## authentication
+/// `auth = ...` takes an expression evaluating to an [`Auth`](crate::auth::Auth). the component is only rendered for
+/// authenticated visitors and all its routes reject unauthorized requests (see [`require_auth`](component::Component::require_auth)).
+/// single routes can be guarded the same way using `#[route("/", POST, auth = ...)]`. see the [`auth`](crate::auth) module
+/// for an example.
+///
+/// ## wrapper element
+/// components are rendered into a `div` by default. `tag = "..."` changes the element, `class = "..."` adds a class
+/// and `attrs = ...` takes anything that iterates over `(name, value)` pairs to set on the element
+/// (see [`tag`](component::Component::tag)). the `fragment` flag renders the component without any wrapper,
+/// keep in mind that its style can't be scoped to the component then (see [`fragment`](component::Component::fragment)).
+///
+/// ```rust
+/// use fishnet::component::prelude::*;
+///
+/// #[component(tag = "nav", class = "sidebar", attrs = [("aria-label", "main navigation")])]
+/// async fn navigation() {
+///     html! {
+///         a href="/" { "home" }
+///     }
+/// }
+///
+/// #[component(fragment)]
+/// async fn list_entry() {
+///     html! {
+///         li { "entry" }
+///     }
+/// }
+/// ```
 pub use fishnet_macros::component;
 
 /// same as [`component`](macro@component), but forces the component to be rerendered each page visit.
 ///
+/// it takes the same arguments as [`component`](macro@component), except for `cache`.
+///
 /// it should be noted that this also forces all parent components to be rendered dynamically!
 pub use fishnet_macros::dyn_component;
 
@@ -1,52 +1,108 @@
 //! A visitable page on the [`Website`](crate::website::Website).
 
 use async_trait::async_trait;
-use axum::{http::header, response::IntoResponse, routing::get, Extension, Router};
+use axum::{
+    body::Body,
+    extract::Path,
+    http::{header, request::Parts, HeaderMap, Uri},
+    response::{IntoResponse, Response},
+    routing::get,
+    Extension, Router,
+};
+use futures::channel::mpsc;
 use futures::future::{BoxFuture, FutureExt};
-use hashbrown::HashSet;
-use maud::{html, Markup, DOCTYPE};
+use hashbrown::{HashMap, HashSet};
+use maud::{html, Markup, PreEscaped, DOCTYPE};
+use std::convert::Infallible;
 use std::sync::Arc;
 use tokio::sync::Mutex;
+use tokio::task::JoinSet;
 use tracing::{debug, debug_span, instrument, Instrument};
 
+use crate::auth::Auth;
+use crate::component::BuiltComponent;
 use crate::css::Stylesheet;
 use crate::js::{self, ScriptType};
 use crate::routes::APIRouter;
+use crate::website::CsrfToken;
 
 pub mod render_context;
-use render_context::ComponentStore;
+use render_context::{ComponentStore, RenderResult};
+
+pub(crate) mod params;
+pub use params::PathParams;
+
+mod request;
+pub use request::RequestContext;
+
+mod error;
+pub use error::RenderError;
+
+mod stream;
+
+mod meta;
+pub use meta::{PageMeta, TwitterCard};
+
+mod layout;
+pub use layout::Layout;
+
+type BodyRenderer =
+    Box<dyn Fn(PathParams) -> BoxFuture<'static, Result<Markup, RenderError>> + Send + Sync>;
 
 pub struct BuiltPage {
-    #[allow(dead_code)]
     name: String,
+    path: String,
 
+    meta: PageMeta,
     head: Markup,
-    body_renderer: Box<dyn Fn() -> BoxFuture<'static, Markup> + Send + Sync>,
+    body_renderer: BodyRenderer,
+    layout: Option<Layout>,
+    streaming: bool,
+    auth: Option<Auth>,
 
-    used_globals: HashSet<String>,
-    components: Arc<Mutex<ComponentStore>>,
+    used_globals: parking_lot::Mutex<HashSet<String>>,
+    // pattern pages can get their own components for every set of parameters
+    components: parking_lot::Mutex<HashMap<PathParams, Arc<parking_lot::Mutex<ComponentStore>>>>,
+    components_per_params: bool,
 
     api_path: String,
+    shared_api_path: String,
     api_router: APIRouter,
 
     script_path: String,
-    bundled_script: String,
+    bundled_script: parking_lot::Mutex<String>,
 
     style_path: String,
-    stylesheet: Stylesheet,
+    stylesheet: parking_lot::Mutex<Stylesheet>,
 
     // tasks that need to be awaited before serving content
-    tasks: Vec<tokio::task::JoinHandle<()>>,
+    tasks: Mutex<Vec<tokio::task::JoinHandle<()>>>,
+    // runners of the components on this page
+    runners: parking_lot::Mutex<JoinSet<()>>,
 }
 
 impl BuiltPage {
+    pub async fn new(page: Page, path: &str) -> (Arc<BuiltPage>, Router) {
+        Self::build(page, path, true).await
+    }
+
+    /// Build a page that is only rendered as a response to errors.
+    ///
+    /// Differently to [`new`](BuiltPage::new), the page itself is not added to the router, only its scripts, styles and api routes are.
+    pub(crate) async fn new_error_page(page: Page, path: &str) -> (Arc<BuiltPage>, Router) {
+        Self::build(page, path, false).await
+    }
+
     #[instrument(name = "Page::build", skip_all, fields(name = %page.name))]
-    pub async fn new(page: Page, path: &str) -> (Arc<Mutex<BuiltPage>>, Router) {
+    async fn build(page: Page, path: &str, route_page: bool) -> (Arc<BuiltPage>, Router) {
         let base_path = path.trim_end_matches('/');
+        let page_path = if base_path.is_empty() { "/" } else { base_path };
         let script_path = format!("{}/script.js", base_path);
         let style_path = format!("{}/style.css", base_path);
 
         let api_path = format!("{}/api", base_path);
+        // components shared by all parameters of a pattern page can't use the parameters in their routes
+        let shared_api_path = params::erase_params(&api_path);
 
         let mut bundled_script = String::new();
         for script in &page.extra_scripts {
@@ -60,70 +116,299 @@ impl BuiltPage {
 
         let built_page = Self {
             name: page.name,
+            path: page_path.to_string(),
 
+            meta: page.meta,
             head: page.head,
             body_renderer: page.body_renderer,
+            layout: page.layout,
+            streaming: page.streaming,
+            auth: page.auth,
 
-            used_globals: HashSet::new(),
-            components: Arc::new(Mutex::new(ComponentStore::new())),
+            used_globals: parking_lot::Mutex::new(HashSet::new()),
+            components: parking_lot::Mutex::new(HashMap::new()),
+            components_per_params: page.components_per_params,
 
             api_path,
+            shared_api_path,
             api_router: APIRouter::new(&format!("{}/api", base_path)),
 
             script_path,
-            bundled_script,
+            bundled_script: parking_lot::Mutex::new(bundled_script),
 
             style_path,
-            stylesheet: Stylesheet::new(),
+            stylesheet: parking_lot::Mutex::new(Stylesheet::new()),
 
-            tasks: Vec::new(),
+            tasks: Mutex::new(Vec::new()),
+            runners: parking_lot::Mutex::new(JoinSet::new()),
         };
 
         let api_router = built_page.api_router.make_router().await;
-        let built_page = Arc::new(Mutex::new(built_page));
+        let built_page = Arc::new(built_page);
         let page_extension = Extension(built_page.clone());
 
         // pre-render the page to save request time. this is obviously not guaranteed to prerender all the components, but it should get most of them.
-        debug!("performing page pre-render");
-        let _ = Self::render(page_extension.clone()).await;
+        // pattern pages can't be pre-rendered, since there are no parameters to render them with.
+        if !built_page.is_pattern() {
+            debug!("performing page pre-render");
+            let _ = Self::render(page_extension.clone()).await;
+        }
 
         debug!("building router");
-        (
-            built_page,
-            Router::new()
-                .route("/", get(BuiltPage::render))
-                .route("/script.js", get(BuiltPage::script))
-                .route("/style.css", get(BuiltPage::style))
-                .merge(api_router)
-                .layer(page_extension),
+        let mut router = Router::new();
+        if route_page {
+            router = router.route(page_path, get(BuiltPage::handle));
+        }
+        let mut router = router
+            .route(&built_page.script_path, get(BuiltPage::script))
+            .route(&built_page.style_path, get(BuiltPage::style))
+            .merge(api_router);
+        if let Some(auth) = &built_page.auth {
+            router = auth.guard_router(router);
+        }
+        let router = router.layer(page_extension);
+
+        (built_page, router)
+    }
+
+    async fn handle(
+        page: Extension<Arc<Self>>,
+        params: Option<Path<Vec<(String, String)>>>,
+        parts: Parts,
+    ) -> Response {
+        let params = params
+            .map(|Path(params)| PathParams::new(params))
+            .unwrap_or_default();
+
+        let request = RequestContext::new(parts, params);
+        if page.streaming {
+            return match Self::render_stream(page, request).await {
+                Ok(body) => {
+                    ([(header::CONTENT_TYPE, "text/html; charset=utf-8")], body).into_response()
+                }
+                Err(error) => error.into_response(),
+            };
+        }
+
+        match Self::render_request(page, request).await {
+            Ok(render) => render.into_response(),
+            Err(error) => error.into_response(),
+        }
+    }
+
+    /// Render the page.
+    ///
+    /// For pages with a pattern path, this renders the page without any parameters. Use [`render_with_params`](BuiltPage::render_with_params) instead.
+    /// Fails if the body or any of the components on the page fail to render.
+    pub async fn render(page: Extension<Arc<Self>>) -> Result<Markup, RenderError> {
+        Self::render_inner(page, PathParams::default(), None).await
+    }
+
+    /// Render the page using the given path parameters.
+    pub async fn render_with_params(
+        page: Extension<Arc<Self>>,
+        params: PathParams,
+    ) -> Result<Markup, RenderError> {
+        Self::render_inner(page, params, None).await
+    }
+
+    /// Render the page for the given request.
+    ///
+    /// The request is made available to the page body and dynamic components through [`render_context::request`].
+    pub async fn render_request(
+        page: Extension<Arc<Self>>,
+        request: RequestContext,
+    ) -> Result<Markup, RenderError> {
+        Self::render_inner(page, request.params().clone(), Some(request)).await
+    }
+
+    async fn render_inner(
+        page: Extension<Arc<Self>>,
+        params: PathParams,
+        request: Option<RequestContext>,
+    ) -> Result<Markup, RenderError> {
+        let start = std::time::Instant::now();
+        let csrf_token = request
+            .as_ref()
+            .and_then(|request| request.csrf_token().cloned());
+
+        let (render, result) = render_context::render_page(
+            &page.api_base(&params),
+            page.component_store(&params),
+            request,
+            page.render_body(&params),
         )
+        .await;
+
+        page.apply_render_result(result).await;
+
+        let render = match render {
+            Ok(render) => render,
+            Err(error) => {
+                debug!("page render failed after {:?}: {}", start.elapsed(), error);
+                return Err(error);
+            }
+        };
+
+        let full_render = html! {
+            (page.document_start(&params, csrf_token.as_ref()))
+            (render)
+            (page.document_end(&params))
+        };
+
+        debug!("page render took {:?}", start.elapsed());
+
+        Ok(full_render)
     }
 
-    pub async fn render(page: Extension<Arc<Mutex<Self>>>) -> Markup {
+    /// Render the page for the given request as a stream.
+    ///
+    /// The page is sent as soon as its body finished rendering. Dynamic components that were already built are rendered
+    /// afterwards and streamed in as they finish (see [`Page::streaming`]).
+    ///
+    /// Errors can only be returned until the body finished rendering. Later errors are logged and leave the component empty.
+    pub async fn render_stream(
+        page: Extension<Arc<Self>>,
+        request: RequestContext,
+    ) -> Result<Body, RenderError> {
         let start = std::time::Instant::now();
+        let params = request.params().clone();
+        let csrf_token = request.csrf_token().cloned();
+
+        let (render, result, deferred) = render_context::render_page_streaming(
+            &page.api_base(&params),
+            page.component_store(&params),
+            Some(request),
+            page.render_body(&params),
+        )
+        .await;
+
+        page.apply_render_result(result).await;
+
+        let render = match render {
+            Ok(render) => render,
+            Err(error) => {
+                debug!("page render failed after {:?}: {}", start.elapsed(), error);
+                return Err(error);
+            }
+        };
+
+        let (sender, receiver) = mpsc::unbounded::<Result<String, Infallible>>();
+        let shell = html! {
+            (page.document_start(&params, csrf_token.as_ref()))
+            (stream::swap_script())
+            (render)
+        };
+        let _ = sender.unbounded_send(Ok(shell.into_string()));
+        debug!(
+            "page shell render took {:?}, streaming {} components",
+            start.elapsed(),
+            deferred.len()
+        );
+
+        let page = page.0.clone();
+        tokio::spawn(
+            async move {
+                let result = deferred
+                    .render(|id, render| {
+                        // the client might have gone away already, the components are still rendered to keep their state consistent
+                        let _ = sender.unbounded_send(Ok(stream::chunk(id, render).into_string()));
+                    })
+                    .await;
+                page.apply_render_result(result).await;
+
+                let _ = sender.unbounded_send(Ok(page.document_end(&params).into_string()));
+                debug!("page stream took {:?}", start.elapsed());
+            }
+            .instrument(debug_span!("Page::stream")),
+        );
+
+        Ok(Body::from_stream(receiver))
+    }
 
-        let mut page_guard = page.lock().await;
+    // render the body of the page and wrap it in its layouts.
+    fn render_body(&self, params: &PathParams) -> BoxFuture<'static, Result<Markup, RenderError>> {
+        let body = (self.body_renderer)(params.clone());
+        let layout = self.layout.clone();
+
+        async move {
+            let content = body.await?;
+            match layout {
+                Some(layout) => Ok(layout.render(content).await),
+                None => Ok(content),
+            }
+        }
+        .boxed()
+    }
 
-        render_context::enter_page(&mut page_guard).await;
-        let render = (page_guard.body_renderer)().await;
-        let mut result = render_context::exit_page().await;
+    // the route the components for the given parameters are built with.
+    fn api_base(&self, params: &PathParams) -> String {
+        match self.components_per_params {
+            true => params::fill_path(&self.api_path, params),
+            false => self.shared_api_path.clone(),
+        }
+    }
 
-        //dbg!(&page.components.lock().unwrap());
+    /// the parameters of the page a request to one of its component routes was sent from.
+    ///
+    /// components that are shared by all parameters don't have them in their route, so they are taken from the url htmx
+    /// sends along (or the referer) instead.
+    pub(crate) fn request_params(
+        &self,
+        route_params: PathParams,
+        headers: &HeaderMap,
+    ) -> PathParams {
+        if self.components_per_params || !self.is_pattern() {
+            return route_params;
+        }
 
-        drop(page_guard);
+        ["hx-current-url", "referer"]
+            .into_iter()
+            .filter_map(|name| headers.get(name)?.to_str().ok()?.parse::<Uri>().ok())
+            .find_map(|uri| params::match_path(&self.path, uri.path()))
+            .unwrap_or_default()
+    }
+
+    // get the components for the given parameters, all parameters share one store unless the page opted into separate ones.
+    fn component_store(&self, params: &PathParams) -> Arc<parking_lot::Mutex<ComponentStore>> {
+        let key = match self.components_per_params {
+            true => params.clone(),
+            false => PathParams::default(),
+        };
+        self.components
+            .lock()
+            .entry(key)
+            .or_insert_with(|| Arc::new(parking_lot::Mutex::new(ComponentStore::new())))
+            .clone()
+    }
+
+    // add everything that was collected during a render to the page.
+    pub(crate) async fn apply_render_result(self: &Arc<Self>, mut result: RenderResult) {
+        // only add the globals that are new to this page
+        let new_globals: Vec<String> = {
+            let mut used_globals = self.used_globals.lock();
+            let mut new_globals: Vec<_> = result
+                .new_components
+                .drain()
+                .filter(|id| used_globals.insert(id.clone()))
+                .collect();
+            if let Some(layout) = &self.layout {
+                for layout in layout.chain() {
+                    new_globals.extend(layout.add_globals_to(&mut used_globals));
+                }
+            }
+            new_globals
+        };
 
         let mut tasks = Vec::new();
         let span = debug_span!("Page::task");
-        for id in result.new_components.drain() {
-            let page = page.clone();
+        for id in new_globals {
+            let page = self.clone();
             tasks.push(tokio::spawn(
                 async move {
-                    if page.lock().await.used_globals.contains(&id) {
-                        return;
-                    }
                     if let Some(component_globals) = render_context::global_store().get(&id).await {
                         if let Some(style) = &component_globals.style {
-                            page.lock().await.stylesheet.add(style);
+                            page.stylesheet.lock().add(style);
                         }
 
                         for script in &component_globals.scripts {
@@ -132,7 +417,7 @@ impl BuiltPage {
                             #[cfg(feature = "minify-js")]
                             let script = &js::minify_script(script).await;
 
-                            page.lock().await.bundled_script.push_str(script.as_str());
+                            page.bundled_script.lock().push_str(script.as_str());
                         }
                     }
                 }
@@ -140,63 +425,145 @@ impl BuiltPage {
             ))
         }
 
-        let mut page = page.lock().await;
-        page.tasks.append(&mut tasks);
+        if !tasks.is_empty() {
+            self.tasks.lock().await.append(&mut tasks);
+        }
 
-        for runner in result.runners {
-            tokio::spawn(runner);
+        if !result.runners.is_empty() {
+            let mut runners = self.runners.lock();
+            for runner in result.runners {
+                runners.spawn(runner);
+            }
         }
 
         for (route, router) in result.routers.drain(..) {
-            page.api_router.add_component(route, router).await;
+            self.api_router.add_component(route, router).await;
         }
+    }
 
-        let full_render = html! {
-                (DOCTYPE)
-                html lang="en" {
-                    head {
-                        (page.head)
-                        link rel="stylesheet" href=(page.style_path) {}
-                    }
-                    (render)
-                    script src=(page.script_path) {}
+    // everything in front of the page body.
+    fn document_start(&self, params: &PathParams, csrf_token: Option<&CsrfToken>) -> Markup {
+        html! {
+            (DOCTYPE)
+            (PreEscaped("<html lang=\"en\">"))
+            head {
+                (self.meta)
+                @if let Some(csrf_token) = csrf_token {
+                    (csrf_token.head())
                 }
-        };
+                (self.head)
+                link rel="stylesheet" href=(params::fill_path(&self.style_path, params)) {}
+            }
+        }
+    }
 
-        debug!("page render took {:?}", start.elapsed());
+    // everything after the page body.
+    fn document_end(&self, params: &PathParams) -> Markup {
+        html! {
+            script src=(params::fill_path(&self.script_path, params)) {}
+            (PreEscaped("</html>"))
+        }
+    }
+
+    /// The name of the page.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
 
-        full_render
+    /// The path the page is served at.
+    pub fn path(&self) -> &str {
+        &self.path
     }
 
-    async fn wait_for_tasks(self: &mut BuiltPage) {
-        let len = self.tasks.len();
+    /// Whether the page [requires authentication](Page::require_auth).
+    pub fn requires_auth(&self) -> bool {
+        self.auth.is_some()
+    }
+
+    /// The layout the page is wrapped in, if any.
+    pub(crate) fn layout(&self) -> Option<&Layout> {
+        self.layout.as_ref()
+    }
+
+    /// Whether the path of the page contains parameters (e.g. `/blog/:slug`).
+    pub fn is_pattern(&self) -> bool {
+        params::is_pattern(&self.path)
+    }
+
+    /// The path the bundled script of the page is served at.
+    pub fn script_path(&self) -> &str {
+        &self.script_path
+    }
+
+    /// The path the stylesheet of the page is served at.
+    pub fn style_path(&self) -> &str {
+        &self.style_path
+    }
+
+    /// All the components that have been built for this page so far.
+    pub fn components(&self) -> Vec<Arc<BuiltComponent>> {
+        self.components
+            .lock()
+            .values()
+            .flat_map(|components| components.lock().0.values().cloned().collect::<Vec<_>>())
+            .collect()
+    }
+
+    /// The api routes of all the components that registered routes on this page.
+    pub async fn api_routes(&self) -> Vec<String> {
+        self.api_router.routes().await
+    }
+
+    /// The bundled script of all the components rendered on this page so far.
+    pub async fn bundled_script(&self) -> String {
+        self.wait_for_tasks().await;
+        self.bundled_script.lock().clone()
+    }
+
+    /// The stylesheet of all the components rendered on this page so far.
+    pub async fn stylesheet(&self) -> String {
+        self.wait_for_tasks().await;
+        self.stylesheet.lock().render()
+    }
+
+    /// Stop all the component runners that were started by this page.
+    ///
+    /// The runners are cancelled at their next `.await` point. Components that get built by later renders will start their runners again.
+    pub async fn stop_runners(&self) {
+        let mut runners = std::mem::take(&mut *self.runners.lock());
+        if runners.is_empty() {
+            return;
+        }
+
+        debug!("stopping {:?} runners", runners.len());
+        runners.shutdown().await;
+    }
+
+    async fn wait_for_tasks(&self) {
+        // keep the lock while waiting so that concurrent requests also wait for the tasks to finish
+        let mut tasks = self.tasks.lock().await;
+        let len = tasks.len();
         if len == 0 {
             return;
         }
 
         debug!("waiting for {:?} tasks to finish", len);
-        futures::future::join_all(self.tasks.drain(..)).await;
+        futures::future::join_all(tasks.drain(..)).await;
     }
 
     // Endpoint for serving the bundled script.
-    async fn script(page: Extension<Arc<Mutex<Self>>>) -> impl IntoResponse {
-        let mut page = page.lock().await;
-        page.wait_for_tasks().await;
-
+    async fn script(page: Extension<Arc<Self>>) -> impl IntoResponse {
         (
             [(header::CONTENT_TYPE, "application/javascript")],
-            page.bundled_script.clone(),
+            page.bundled_script().await,
         )
     }
 
     // Endpoint for serving the stylesheet.
-    async fn style(page: Extension<Arc<Mutex<Self>>>) -> impl IntoResponse {
-        let mut page = page.lock().await;
-        page.wait_for_tasks().await;
-
+    async fn style(page: Extension<Arc<Self>>) -> impl IntoResponse {
         (
             [(header::CONTENT_TYPE, "text/css")],
-            page.stylesheet.render(),
+            page.stylesheet().await,
         )
     }
 }
@@ -207,8 +574,13 @@ impl BuiltPage {
 pub struct Page {
     name: String,
 
+    meta: PageMeta,
     head: Markup,
-    body_renderer: Box<dyn Fn() -> BoxFuture<'static, Markup> + Send + Sync>,
+    body_renderer: BodyRenderer,
+    layout: Option<Layout>,
+    streaming: bool,
+    components_per_params: bool,
+    auth: Option<Auth>,
 
     extra_scripts: HashSet<ScriptType>,
 }
@@ -224,31 +596,191 @@ impl Page {
         Self {
             name: name.into(),
 
+            meta: PageMeta::new(),
             head: html! {},
-            body_renderer: Box::new(|| {
-                async {
-                    html! {}
-                }
-                .boxed()
-            }),
+            body_renderer: Box::new(|_| async { Ok(html! {}) }.boxed()),
+            layout: None,
+            streaming: false,
+            components_per_params: false,
+            auth: None,
 
             extra_scripts,
         }
     }
 
+    /// Add custom markup to the `head` of the page.
+    ///
+    /// For the common meta tags, use [`with_meta`](Page::with_meta) instead.
     pub fn with_head(mut self, head: Markup) -> Self {
         self.head = head;
         self
     }
 
+    /// Set the metadata (title, description, previews, ...) of the page.
+    ///
+    /// Everything that is not set falls back to the [default metadata](crate::Website::default_meta) of the website.
+    /// ```rust
+    /// use fishnet::Page;
+    /// use fishnet::page::PageMeta;
+    ///
+    /// Page::new("about").with_meta(
+    ///     PageMeta::new()
+    ///         .title("about us")
+    ///         .description("everything about fishnet"),
+    /// );
+    /// ```
+    pub fn with_meta(mut self, meta: PageMeta) -> Self {
+        self.meta = meta;
+        self
+    }
+
+    pub(crate) fn with_default_meta(mut self, defaults: &PageMeta) -> Self {
+        self.meta = self.meta.with_defaults(defaults);
+        self
+    }
+
+    /// Wrap the content of the page in a [`Layout`].
+    ///
+    /// Components within the layout are shared with all the other pages using the same layout.
+    /// Their api routes are only served if the page is added to a [`Website`](crate::Website).
+    pub fn with_layout(mut self, layout: &Layout) -> Self {
+        self.layout = Some(layout.clone());
+        self
+    }
+
+    pub(crate) fn layout(&self) -> Option<&Layout> {
+        self.layout.as_ref()
+    }
+
+    /// Only serve the page to authenticated visitors.
+    ///
+    /// Unauthorized requests to the page, its scripts and the api routes of its components are rejected with `401 Unauthorized`
+    /// or [redirected](Auth::redirect_to). The name of the authenticated user can be read using [`RequestContext::user`].
+    /// ```rust
+    /// use fishnet::Page;
+    /// use fishnet::auth::{Auth, TokenAuth};
+    ///
+    /// let auth = Auth::new(TokenAuth::new().token("very secret token", "admin").cookie("token"))
+    ///     .redirect_to("/login");
+    /// Page::new("admin").require_auth(auth);
+    /// ```
+    pub fn require_auth(mut self, auth: Auth) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    /// Enable or disable streaming the page.
+    ///
+    /// A streamed page is sent as soon as its body finished rendering, which keeps the time until the first byte arrives low.
+    /// Dynamic components are rendered afterwards and streamed in as they finish, while static components are part of the page right away.
+    /// This helps with pages containing a few slow dynamic components.
+    ///
+    /// Only components that were built during an earlier render are streamed, so the very first render of a component happens in place.
+    /// Since the status code is sent with the page, errors of streamed components can't fail the response anymore (see [`RenderError`]).
+    /// Streamed components are inserted using inline scripts, so clients need javascript enabled to see them.
+    pub fn streaming(mut self, enable: bool) -> Self {
+        self.streaming = enable;
+        self
+    }
+
+    /// Give every set of path parameters its own components.
+    ///
+    /// By default, all the parameters of a [pattern page](Page::with_param_body) share the same components, so components that
+    /// depend on the parameters need a [key](crate::c!#rendering-lists) (e.g. `c!(key = slug, blog_post(slug))`). With this enabled, every set of
+    /// parameters gets its own components (and state) instead.
+    ///
+    /// Shared components serve their api routes without the parameters (e.g. `/blog/_/api/...` for a page at `/blog/:slug`),
+    /// their [render route](crate::component::Component::render_route) gets the parameters from the page the request was sent from.
+    ///
+    /// The components are kept for as long as the website runs, so this should only be used if the possible parameters are known
+    /// in advance (e.g. the pages of a documentation). Otherwise, every visit of a new path builds new components.
+    pub fn components_per_params(mut self) -> Self {
+        self.components_per_params = true;
+        self
+    }
+
     /// Add content to the page.
     ///
     /// This function takes in a closure that returns a rendered page.
-    pub fn with_body<C>(mut self, content_renderer: C) -> Self
+    pub fn with_body<C>(self, content_renderer: C) -> Self
     where
         C: Fn() -> BoxFuture<'static, Markup> + Send + Sync + 'static,
     {
-        self.body_renderer = Box::new(content_renderer);
+        self.try_with_param_body(move |_| content_renderer().map(Ok::<_, Infallible>).boxed())
+    }
+
+    /// Add content to the page that can fail to render.
+    ///
+    /// If the closure returns an error, the page responds with the error instead (see [`RenderError`]).
+    /// Anything implementing [`IntoResponse`] can be used as the error.
+    /// ```rust
+    /// use fishnet::{Page, html};
+    /// use fishnet::page::RenderError;
+    /// use axum::http::StatusCode;
+    /// use futures::future::FutureExt;
+    ///
+    /// async fn load_posts() -> Result<Vec<String>, std::io::Error> {
+    ///     Ok(vec!["hello world".to_string()])
+    /// }
+    ///
+    /// Page::new("blog").try_with_body(|| async {
+    ///     let posts = load_posts()
+    ///         .await
+    ///         .map_err(|err| RenderError::new(StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
+    ///
+    ///     Ok::<_, RenderError>(html! {
+    ///         @for post in posts {
+    ///             p { (post) }
+    ///         }
+    ///     })
+    /// }.boxed());
+    /// ```
+    pub fn try_with_body<C, E>(self, content_renderer: C) -> Self
+    where
+        C: Fn() -> BoxFuture<'static, Result<Markup, E>> + Send + Sync + 'static,
+        E: IntoResponse + 'static,
+    {
+        self.try_with_param_body(move |_| content_renderer())
+    }
+
+    /// Add content to the page that depends on the parameters of the page path.
+    ///
+    /// This is meant for pages added with a pattern path like `/blog/:slug`. The closure receives the [`PathParams`] of the visited path.
+    /// Components are shared between all parameters, use a [key](crate::c!#rendering-lists) for components that depend on them
+    /// (or see [`components_per_params`](Page::components_per_params)).
+    /// ```rust
+    /// use fishnet::{Page, html};
+    /// use futures::future::FutureExt;
+    ///
+    /// Page::new("blog").with_param_body(|params| async move {
+    ///     html! {
+    ///         h1 { "post: " (params.get("slug").unwrap_or_default()) }
+    ///     }
+    /// }.boxed());
+    /// ```
+    pub fn with_param_body<C>(self, content_renderer: C) -> Self
+    where
+        C: Fn(PathParams) -> BoxFuture<'static, Markup> + Send + Sync + 'static,
+    {
+        self.try_with_param_body(move |params| {
+            content_renderer(params).map(Ok::<_, Infallible>).boxed()
+        })
+    }
+
+    /// Add content to the page that depends on the parameters of the page path and can fail to render.
+    ///
+    /// This combines [`with_param_body`](Page::with_param_body) and [`try_with_body`](Page::try_with_body).
+    /// Use [`RenderError::not_found`] for parameters that have no content.
+    pub fn try_with_param_body<C, E>(mut self, content_renderer: C) -> Self
+    where
+        C: Fn(PathParams) -> BoxFuture<'static, Result<Markup, E>> + Send + Sync + 'static,
+        E: IntoResponse + 'static,
+    {
+        self.body_renderer = Box::new(move |params| {
+            content_renderer(params)
+                .map(|render| render.map_err(RenderError::from_response))
+                .boxed()
+        });
         self
     }
 }
@@ -263,6 +795,6 @@ pub trait RouterPageExt {
 #[async_trait]
 impl RouterPageExt for Router {
     async fn attach_page(self, path: &str, page: Page) -> Self {
-        BuiltPage::new(page, path).await.1
+        self.merge(BuiltPage::new(page, path).await.1)
     }
 }
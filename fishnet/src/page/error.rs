@@ -0,0 +1,69 @@
+//! errors that can occur while rendering a page.
+
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use std::fmt;
+
+use crate::routes;
+
+/// an error that aborted the render of a page.
+///
+/// the error is turned into the response of the request instead of the rendered page. anything implementing
+/// [`IntoResponse`] can be used as an error by fallible page bodies and components, it gets converted using
+/// [`from_response`](RenderError::from_response).
+///
+/// responses that aren't html and have a server error status (`5xx`) are replaced by the
+/// [error page](crate::Website::error_page) of the website if there is one.
+pub struct RenderError(Box<Response>);
+
+impl RenderError {
+    /// create a new error with the given status and a plain text message.
+    pub fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self::from_response((status, message.into()))
+    }
+
+    /// create a new error responding with the given response.
+    pub fn from_response(response: impl IntoResponse) -> Self {
+        Self(Box::new(response.into_response()))
+    }
+
+    /// create an error that shows the [not found page](crate::Website::not_found_page) of the website.
+    ///
+    /// this is useful for pattern pages, where not every value of a parameter has content to show.
+    pub fn not_found() -> Self {
+        Self::from_response(routes::not_found(""))
+    }
+
+    /// the status code the error responds with.
+    pub fn status(&self) -> StatusCode {
+        self.0.status()
+    }
+}
+
+impl From<StatusCode> for RenderError {
+    fn from(status: StatusCode) -> Self {
+        Self::from_response(status)
+    }
+}
+
+impl IntoResponse for RenderError {
+    fn into_response(self) -> Response {
+        *self.0
+    }
+}
+
+impl fmt::Debug for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RenderError").field(&self.status()).finish()
+    }
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "render failed with status {}", self.status())
+    }
+}
+
+impl std::error::Error for RenderError {}
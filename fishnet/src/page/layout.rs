@@ -0,0 +1,219 @@
+//! layouts wrapping the content of multiple pages.
+
+use axum::Router;
+use futures::future::{BoxFuture, FutureExt};
+use hashbrown::HashSet;
+use maud::Markup;
+use std::sync::Arc;
+use tokio::task::JoinSet;
+use tracing::debug;
+
+use super::render_context::{self, ComponentStore};
+use crate::component::BuiltComponent;
+use crate::routes::APIRouter;
+
+type LayoutRenderer = Box<dyn Fn(Markup) -> BoxFuture<'static, Markup> + Send + Sync>;
+
+/// a layout shared by multiple pages, like a header, navigation and footer around the page content.
+///
+/// the layout receives the rendered content of the page and places it wherever it wants.
+/// components within the layout are shared between all the pages using it, so e.g. a navigation component is only built once
+/// and keeps its state across pages.
+///
+/// layouts are cheap to clone, every clone refers to the same layout.
+/// ```rust
+/// use fishnet::{Page, html};
+/// use fishnet::page::Layout;
+/// use futures::future::FutureExt;
+///
+/// let site = Layout::new("site", |content| async move {
+///     html! {
+///         nav { a href="/" { "home" } }
+///         main { (content) }
+///     }
+/// }.boxed());
+///
+/// let docs = site.nest("docs", |content| async move {
+///     html! {
+///         aside { "table of contents" }
+///         article { (content) }
+///     }
+/// }.boxed());
+///
+/// Page::new("home").with_layout(&site);
+/// Page::new("getting started").with_layout(&docs);
+/// ```
+#[derive(Clone)]
+pub struct Layout(Arc<LayoutInner>);
+
+struct LayoutInner {
+    name: String,
+    renderer: LayoutRenderer,
+    parent: Option<Layout>,
+
+    path: String,
+    api_path: String,
+    api_router: APIRouter,
+    components: Arc<parking_lot::Mutex<ComponentStore>>,
+
+    // globals of the components within the layout, every page using the layout needs them
+    globals: parking_lot::Mutex<HashSet<String>>,
+    runners: parking_lot::Mutex<JoinSet<()>>,
+}
+
+impl Layout {
+    /// create a new layout.
+    ///
+    /// the api routes of the components within the layout are served under its name,
+    /// so every layout of a website needs a different one.
+    pub fn new<C>(name: &str, renderer: C) -> Self
+    where
+        C: Fn(Markup) -> BoxFuture<'static, Markup> + Send + Sync + 'static,
+    {
+        Self::build(name, Box::new(renderer), None)
+    }
+
+    /// create a new layout that is placed within this one.
+    ///
+    /// pages using the returned layout are wrapped by it first and by this layout afterwards.
+    /// its api routes are served below the ones of this layout, so the name only has to differ from the other layouts nested in this one.
+    pub fn nest<C>(&self, name: &str, renderer: C) -> Self
+    where
+        C: Fn(Markup) -> BoxFuture<'static, Markup> + Send + Sync + 'static,
+    {
+        Self::build(name, Box::new(renderer), Some(self.clone()))
+    }
+
+    fn build(name: &str, renderer: LayoutRenderer, parent: Option<Layout>) -> Self {
+        // layouts don't have a path of their own, so their components get one derived from the names of the layout chain.
+        // it has to stay the same between restarts, clients might still use the old routes.
+        let path = match &parent {
+            Some(parent) => format!("{}/{}", parent.0.path, path_segment(name)),
+            None => format!("/_fishnet/layout/{}", path_segment(name)),
+        };
+        let api_path = format!("{}/api", path);
+
+        Self(Arc::new(LayoutInner {
+            name: name.to_string(),
+            renderer,
+            parent,
+
+            path,
+            api_router: APIRouter::new(&api_path),
+            api_path,
+            components: Arc::new(parking_lot::Mutex::new(ComponentStore::new())),
+
+            globals: parking_lot::Mutex::new(HashSet::new()),
+            runners: parking_lot::Mutex::new(JoinSet::new()),
+        }))
+    }
+
+    /// the name of the layout.
+    pub fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    /// the layout this layout is placed in, if any.
+    pub fn parent(&self) -> Option<&Layout> {
+        self.0.parent.as_ref()
+    }
+
+    /// whether both layouts refer to the same layout.
+    pub(crate) fn ptr_eq(&self, other: &Layout) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+
+    /// this layout followed by all the layouts it is nested in.
+    pub(crate) fn chain(&self) -> impl Iterator<Item = &Layout> {
+        std::iter::successors(Some(self), |layout| layout.parent())
+    }
+
+    /// wrap the content with this layout and all its parents.
+    ///
+    /// this has to be called during a page render.
+    pub(crate) fn render(&self, content: Markup) -> BoxFuture<'_, Markup> {
+        async move {
+            let (render, mut result) = render_context::render_scoped(
+                &self.0.api_path,
+                self.0.components.clone(),
+                (self.0.renderer)(content),
+            )
+            .await;
+
+            if !result.new_components.is_empty() {
+                self.0.globals.lock().extend(result.new_components.drain());
+            }
+            if !result.runners.is_empty() {
+                let mut runners = self.0.runners.lock();
+                for runner in result.runners {
+                    runners.spawn(runner);
+                }
+            }
+            for (route, router) in result.routers {
+                self.0.api_router.add_component(route, router).await;
+            }
+
+            match &self.0.parent {
+                Some(parent) => parent.render(render).await,
+                None => render,
+            }
+        }
+        .boxed()
+    }
+
+    /// add the ids of the globals used by the components within the layout to `used`.
+    ///
+    /// returns the ids that weren't in `used` yet.
+    pub(crate) fn add_globals_to(&self, used: &mut HashSet<String>) -> Vec<String> {
+        let mut new_globals = Vec::new();
+        for id in self.0.globals.lock().iter() {
+            if !used.contains(id) {
+                used.insert(id.clone());
+                new_globals.push(id.clone());
+            }
+        }
+        new_globals
+    }
+
+    /// the path the api routes of the components within the layout are served under.
+    pub(crate) fn api_path(&self) -> &str {
+        &self.0.api_path
+    }
+
+    /// all the components that have been built within the layout so far.
+    pub(crate) fn components(&self) -> Vec<Arc<BuiltComponent>> {
+        self.0.components.lock().0.values().cloned().collect()
+    }
+
+    /// the api routes of all the components within the layout.
+    pub(crate) async fn api_routes(&self) -> Vec<String> {
+        self.0.api_router.routes().await
+    }
+
+    /// the router serving the api routes of the components within the layout.
+    pub(crate) async fn make_router(&self) -> Router {
+        self.0.api_router.make_router().await
+    }
+
+    /// stop all the component runners started by this layout.
+    pub(crate) async fn stop_runners(&self) {
+        let mut runners = std::mem::take(&mut *self.0.runners.lock());
+        if runners.is_empty() {
+            return;
+        }
+
+        debug!("stopping {:?} layout runners", runners.len());
+        runners.shutdown().await;
+    }
+}
+
+// turn a layout name into a single, url safe path segment.
+fn path_segment(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            'a'..='z' | '0'..='9' | '-' | '_' => c,
+            'A'..='Z' => c.to_ascii_lowercase(),
+            _ => '-',
+        })
+        .collect()
+}
@@ -0,0 +1,238 @@
+//! metadata of a page (title, description, social media previews, ...).
+
+use maud::{html, Markup, Render};
+
+/// the kind of preview card shown when a page is shared on twitter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TwitterCard {
+    Summary,
+    SummaryLargeImage,
+    App,
+    Player,
+}
+
+impl TwitterCard {
+    fn as_str(&self) -> &'static str {
+        match self {
+            TwitterCard::Summary => "summary",
+            TwitterCard::SummaryLargeImage => "summary_large_image",
+            TwitterCard::App => "app",
+            TwitterCard::Player => "player",
+        }
+    }
+}
+
+/// the metadata of a page, rendered into its `head`.
+///
+/// the title, description, url and image are also used for the OpenGraph and Twitter previews of the page.
+/// ```rust
+/// use fishnet::page::{PageMeta, TwitterCard};
+///
+/// let meta = PageMeta::new()
+///     .title("my blog")
+///     .description("a blog about fish")
+///     .canonical("https://example.com/blog")
+///     .image("https://example.com/fish.png")
+///     .twitter_card(TwitterCard::SummaryLargeImage);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PageMeta {
+    title: Option<String>,
+    description: Option<String>,
+    canonical: Option<String>,
+
+    image: Option<String>,
+    og_type: Option<String>,
+    site_name: Option<String>,
+    twitter_card: Option<TwitterCard>,
+    twitter_site: Option<String>,
+
+    robots: Option<String>,
+    favicon: Option<String>,
+    theme_color: Option<String>,
+}
+
+impl PageMeta {
+    /// create new metadata without anything set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// set the title of the page.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// set the description of the page, shown by search engines and in previews.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// set the canonical url of the page. this is also used as the OpenGraph url.
+    pub fn canonical(mut self, url: impl Into<String>) -> Self {
+        self.canonical = Some(url.into());
+        self
+    }
+
+    /// set the url of the image shown in previews.
+    pub fn image(mut self, url: impl Into<String>) -> Self {
+        self.image = Some(url.into());
+        self
+    }
+
+    /// set the OpenGraph type of the page (e.g. `website` or `article`).
+    pub fn og_type(mut self, og_type: impl Into<String>) -> Self {
+        self.og_type = Some(og_type.into());
+        self
+    }
+
+    /// set the name of the site the page belongs to.
+    pub fn site_name(mut self, site_name: impl Into<String>) -> Self {
+        self.site_name = Some(site_name.into());
+        self
+    }
+
+    /// set the kind of twitter preview card.
+    pub fn twitter_card(mut self, card: TwitterCard) -> Self {
+        self.twitter_card = Some(card);
+        self
+    }
+
+    /// set the twitter handle of the site (e.g. `@fishnet`).
+    pub fn twitter_site(mut self, handle: impl Into<String>) -> Self {
+        self.twitter_site = Some(handle.into());
+        self
+    }
+
+    /// set the instructions for search engine crawlers (e.g. `noindex, nofollow`).
+    pub fn robots(mut self, robots: impl Into<String>) -> Self {
+        self.robots = Some(robots.into());
+        self
+    }
+
+    /// set the url of the icon of the page.
+    pub fn favicon(mut self, url: impl Into<String>) -> Self {
+        self.favicon = Some(url.into());
+        self
+    }
+
+    /// set the color browsers use for their ui around the page.
+    pub fn theme_color(mut self, color: impl Into<String>) -> Self {
+        self.theme_color = Some(color.into());
+        self
+    }
+
+    /// fill everything that is not set with the values of `defaults`.
+    pub fn with_defaults(self, defaults: &PageMeta) -> Self {
+        Self {
+            title: self.title.or_else(|| defaults.title.clone()),
+            description: self.description.or_else(|| defaults.description.clone()),
+            canonical: self.canonical.or_else(|| defaults.canonical.clone()),
+
+            image: self.image.or_else(|| defaults.image.clone()),
+            og_type: self.og_type.or_else(|| defaults.og_type.clone()),
+            site_name: self.site_name.or_else(|| defaults.site_name.clone()),
+            twitter_card: self.twitter_card.or(defaults.twitter_card),
+            twitter_site: self.twitter_site.or_else(|| defaults.twitter_site.clone()),
+
+            robots: self.robots.or_else(|| defaults.robots.clone()),
+            favicon: self.favicon.or_else(|| defaults.favicon.clone()),
+            theme_color: self.theme_color.or_else(|| defaults.theme_color.clone()),
+        }
+    }
+}
+
+impl Render for PageMeta {
+    fn render(&self) -> Markup {
+        html! {
+            @if let Some(title) = &self.title {
+                title { (title) }
+                meta property="og:title" content=(title);
+                meta name="twitter:title" content=(title);
+            }
+            @if let Some(description) = &self.description {
+                meta name="description" content=(description);
+                meta property="og:description" content=(description);
+                meta name="twitter:description" content=(description);
+            }
+            @if let Some(canonical) = &self.canonical {
+                link rel="canonical" href=(canonical);
+                meta property="og:url" content=(canonical);
+            }
+
+            @if let Some(image) = &self.image {
+                meta property="og:image" content=(image);
+                meta name="twitter:image" content=(image);
+            }
+            @if let Some(og_type) = &self.og_type {
+                meta property="og:type" content=(og_type);
+            }
+            @if let Some(site_name) = &self.site_name {
+                meta property="og:site_name" content=(site_name);
+            }
+            @if let Some(card) = &self.twitter_card {
+                meta name="twitter:card" content=(card.as_str());
+            }
+            @if let Some(handle) = &self.twitter_site {
+                meta name="twitter:site" content=(handle);
+            }
+
+            @if let Some(robots) = &self.robots {
+                meta name="robots" content=(robots);
+            }
+            @if let Some(favicon) = &self.favicon {
+                link rel="icon" href=(favicon);
+            }
+            @if let Some(color) = &self.theme_color {
+                meta name="theme-color" content=(color);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let meta = PageMeta::new()
+            .title("fish & chips")
+            .canonical("https://example.com/")
+            .twitter_card(TwitterCard::Summary);
+
+        assert_eq!(
+            meta.render().into_string(),
+            "<title>fish &amp; chips</title>\
+             <meta property=\"og:title\" content=\"fish &amp; chips\">\
+             <meta name=\"twitter:title\" content=\"fish &amp; chips\">\
+             <link rel=\"canonical\" href=\"https://example.com/\">\
+             <meta property=\"og:url\" content=\"https://example.com/\">\
+             <meta name=\"twitter:card\" content=\"summary\">"
+        );
+        assert_eq!(PageMeta::new().render().into_string(), "");
+    }
+
+    #[test]
+    fn test_with_defaults() {
+        let defaults = PageMeta::new()
+            .title("fishnet")
+            .site_name("fishnet")
+            .theme_color("#000000");
+        let meta = PageMeta::new()
+            .title("blog")
+            .description("about fish")
+            .with_defaults(&defaults);
+
+        assert_eq!(
+            meta,
+            PageMeta::new()
+                .title("blog")
+                .description("about fish")
+                .site_name("fishnet")
+                .theme_color("#000000")
+        );
+    }
+}
@@ -0,0 +1,172 @@
+//! path parameters of pages with a pattern path (e.g. `/blog/:slug`).
+
+use std::sync::Arc;
+
+/// the parameters extracted from the path of a pattern page.
+///
+/// for a page added at `/blog/:slug`, visiting `/blog/hello-world` results in the parameter `slug`
+/// being set to `hello-world`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct PathParams(Arc<[(String, String)]>);
+
+impl PathParams {
+    /// create a new set of parameters from `(name, value)` pairs.
+    pub fn new<I>(params: I) -> Self
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        let mut params: Vec<_> = params.into_iter().collect();
+        // keep the order stable, so equal parameters always hash the same
+        params.sort();
+
+        Self(params.into())
+    }
+
+    /// get the value of the parameter with the given name.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(param, _)| param == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// iterate over all `(name, value)` pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    /// whether there are no parameters at all.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// whether the given path contains any parameters.
+pub(crate) fn is_pattern(path: &str) -> bool {
+    path.split('/')
+        .any(|segment| segment.starts_with(':') || segment.starts_with('*'))
+}
+
+/// replace all the parameters in `pattern` with their values.
+///
+/// parameters without a value are left untouched.
+pub(crate) fn fill_path(pattern: &str, params: &PathParams) -> String {
+    if params.is_empty() {
+        return pattern.to_string();
+    }
+
+    pattern
+        .split('/')
+        .map(|segment| match segment.strip_prefix([':', '*']) {
+            Some(name) => params.get(name).unwrap_or(segment),
+            None => segment,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// replace all the parameters in `pattern` with `_`, for paths that are the same for every set of parameters.
+pub(crate) fn erase_params(pattern: &str) -> String {
+    pattern
+        .split('/')
+        .map(|segment| match segment.starts_with([':', '*']) {
+            true => "_",
+            false => segment,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// get the parameters of `path`, if it matches `pattern`.
+pub(crate) fn match_path(pattern: &str, path: &str) -> Option<PathParams> {
+    let mut params = Vec::new();
+    let mut segments = path.trim_end_matches('/').split('/');
+    for part in pattern.trim_end_matches('/').split('/') {
+        if let Some(name) = part.strip_prefix('*') {
+            let rest = segments.by_ref().collect::<Vec<_>>().join("/");
+            params.push((name.to_string(), rest));
+            break;
+        }
+
+        let segment = segments.next()?;
+        match part.strip_prefix(':') {
+            Some(name) if !segment.is_empty() => {
+                params.push((name.to_string(), segment.to_string()))
+            }
+            Some(_) => return None,
+            None if part == segment => {}
+            None => return None,
+        }
+    }
+    if segments.next().is_some() {
+        return None;
+    }
+
+    Some(PathParams::new(params))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params() -> PathParams {
+        PathParams::new([
+            ("slug".to_string(), "hello-world".to_string()),
+            ("year".to_string(), "2024".to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_get() {
+        let params = params();
+        assert_eq!(params.get("slug"), Some("hello-world"));
+        assert_eq!(params.get("year"), Some("2024"));
+        assert_eq!(params.get("month"), None);
+    }
+
+    #[test]
+    fn test_is_pattern() {
+        assert!(is_pattern("/blog/:slug"));
+        assert!(is_pattern("/files/*path"));
+        assert!(!is_pattern("/blog"));
+        assert!(!is_pattern("/"));
+    }
+
+    #[test]
+    fn test_fill_path() {
+        let params = params();
+        assert_eq!(
+            fill_path("/blog/:year/:slug/api", &params),
+            "/blog/2024/hello-world/api"
+        );
+        assert_eq!(fill_path("/blog/:month", &params), "/blog/:month");
+        assert_eq!(fill_path("/about", &params), "/about");
+    }
+
+    #[test]
+    fn test_erase_params() {
+        assert_eq!(erase_params("/blog/:year/:slug/api"), "/blog/_/_/api");
+        assert_eq!(erase_params("/about/api"), "/about/api");
+    }
+
+    #[test]
+    fn test_match_path() {
+        assert_eq!(
+            match_path("/blog/:year/:slug", "/blog/2024/hello-world"),
+            Some(params())
+        );
+        assert_eq!(
+            match_path("/files/*path", "/files/a/b.txt"),
+            Some(PathParams::new([(
+                "path".to_string(),
+                "a/b.txt".to_string()
+            )]))
+        );
+        assert_eq!(match_path("/blog/:slug", "/blog"), None);
+        assert_eq!(match_path("/blog/:slug", "/blog/a/b"), None);
+        assert_eq!(match_path("/blog/:slug", "/posts/a"), None);
+        assert_eq!(match_path("/about", "/about/"), Some(PathParams::default()));
+    }
+}
@@ -3,34 +3,56 @@
 //! the render context is used under the hood whenever you use macros that add things to the
 //! page (e.g. [`c!`](crate::c!), [`style!`](crate::style!), [`script!`](crate::script!), ...).
 //!
-//! before a page renders its contents, it attaches itself to the render context via [`enter_page`]. during the
+//! a page renders its contents inside of a render context using [`render_page`]. the context is
+//! local to the task that performs the render, so multiple pages (or the same page multiple times)
+//! can be rendered concurrently without interfering with each other. during the
 //! render when a resource is about to be added, it is first checked whether it already exists on
 //! the render context. if yes, it is not added again and just reused. otherwise it is newly
-//! constructed. after the render is finished, the page can use [`exit_page`] to get a list of all
-//! the newly constructed things during the render and then process them further (e.g. add routes
+//! constructed. after the render is finished, the page gets a [`RenderResult`] containing all
+//! the newly constructed things during the render and can then process them further (e.g. add routes
 //! from new components, minify added scripts, ...)
 //!
+//! since the context is bound to the rendering task, anything that is
+//! [spawned](tokio::spawn) from within a render will not be able to access it.
+//!
 //! you usually don't need to call anything from in here manually unless you want to have finer
 //! control over resources (like dynamically adding resources to the page)
 
-use axum::routing::Router;
-use futures::future::BoxFuture;
+use axum::{response::IntoResponse, routing::Router};
+use futures::future::{BoxFuture, FutureExt};
+use futures::stream::{FuturesUnordered, StreamExt};
 use hashbrown::{hash_map::Entry, HashMap, HashSet};
-use maud::{html, Markup};
+use maud::{html, Markup, PreEscaped};
+use std::cell::RefCell;
+use std::future::Future;
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::sync::{Arc, OnceLock};
-use tokio::sync::{Mutex, OwnedMutexGuard};
+use tokio::sync::Mutex;
 
-use tracing::{error, trace, warn};
+use tracing::{debug, error, trace, warn};
 
-use crate::component::{BuildableComponent, BuiltComponent};
-use crate::page::BuiltPage;
+use crate::component::{BuildableComponent, BuiltComponent, Invalidation};
+use crate::page::{stream, RenderError, RequestContext};
 use crate::routes::ComponentRoute;
 use crate::{css, js};
 
-fn render_context() -> &'static parking_lot::Mutex<Option<RenderContext>> {
-    static RENDER_CONTEXT: parking_lot::Mutex<Option<RenderContext>> =
-        parking_lot::const_mutex(None);
-    &RENDER_CONTEXT
+tokio::task_local! {
+    static RENDER_CONTEXT: RefCell<RenderContext>;
+    // the slots of the component that is currently rendered. this is scoped to the render future of the component
+    // instead of being part of the render context, since deferred components are rendered concurrently.
+    static SLOTS: Slots;
+}
+
+/// run `f` on the render context of the current task.
+///
+/// returns `None` if the current task is not rendering a page.
+fn with_render_context<F, R>(f: F) -> Option<R>
+where
+    F: FnOnce(&mut RenderContext) -> R,
+{
+    RENDER_CONTEXT
+        .try_with(|context| f(&mut context.borrow_mut()))
+        .ok()
 }
 
 /// acquire access to the [`GlobalStore`].
@@ -39,9 +61,10 @@ pub fn global_store() -> &'static GlobalStore {
     GLOBAL_STORE.get_or_init(|| GlobalStore::new())
 }
 
-#[derive(Debug)]
+/// the components of a page, stored by their context id and the hash of their key (if they were rendered using a key).
+#[derive(Debug, Default)]
 #[doc(hidden)]
-pub struct ComponentStore(pub HashMap<&'static str, Arc<BuiltComponent>>);
+pub struct ComponentStore(pub HashMap<(&'static str, Option<u64>), Arc<BuiltComponent>>);
 
 impl ComponentStore {
     pub(crate) fn new() -> Self {
@@ -69,23 +92,20 @@ impl GlobalStore {
     /// does nothing if there already is a entry under the given id.
     /// otherwise the `globals` closure is executed to create a new entry
     ///
-    /// this will also notify the currently active render context about the newly added resource
-    /// (which usually means that it gets added to the rendered page)
+    /// this will also notify the render context of the current task about the resource, even if it
+    /// already existed (which usually means that it gets added to the rendered page)
     pub async fn add<F>(&self, id: &str, globals: F)
     where
         F: FnOnce() -> GlobalStoreEntry,
     {
         let mut store = self.0.lock().await;
-        match store.entry(id.to_string()) {
-            Entry::Vacant(entry) => {
-                entry.insert(Arc::new(globals()));
-                let mut context = render_context().lock();
-                if context.is_some() {
-                    context.as_mut().unwrap().notify_global(&id)
-                }
-            }
-            Entry::Occupied(_) => {}
+        if let Entry::Vacant(entry) = store.entry(id.to_string()) {
+            entry.insert(Arc::new(globals()));
         }
+        drop(store);
+
+        // other pages might have added the resource already, so the current page still needs to know about it
+        with_render_context(|context| context.notify_global(id));
     }
 
     /// get a [`GlobalStoreEntry`] from its id.
@@ -100,45 +120,84 @@ impl GlobalStore {
 }
 
 pub(crate) struct RenderContext {
-    base_route: String,
+    request: Option<RequestContext>,
 
-    components: OwnedMutexGuard<ComponentStore>,
-
-    new_globals: HashSet<String>,
+    scope: RenderScope,
 
     static_state: bool,
     temporary_render_depth: usize,
+    // the static components currently being pre-rendered, innermost last
+    static_parents: Vec<Invalidation>,
 
-    new_runners: Vec<BoxFuture<'static, ()>>,
-    new_routers: Vec<(ComponentRoute, Router)>,
+    // only the first error is kept, the count is used to detect failures of nested renders
+    error: Option<RenderError>,
+    error_count: usize,
 }
 impl RenderContext {
-    async fn new(base_route: &str, components: Arc<Mutex<ComponentStore>>) -> RenderContext {
+    fn new(
+        base_route: &str,
+        components: Arc<parking_lot::Mutex<ComponentStore>>,
+        request: Option<RequestContext>,
+    ) -> RenderContext {
         Self {
-            base_route: base_route.to_string(),
+            request,
 
-            components: components.lock_owned().await,
+            scope: RenderScope::new(base_route, components),
 
             static_state: false,
             temporary_render_depth: 0,
+            static_parents: Vec::new(),
 
+            error: None,
+            error_count: 0,
+        }
+    }
+
+    fn notify_global(&mut self, id: &str) {
+        self.scope.new_globals.insert(id.to_string());
+    }
+
+    fn fail(&mut self, error: RenderError) {
+        self.error_count += 1;
+        if self.error.is_none() {
+            self.error = Some(error);
+        }
+    }
+}
+
+/// the part of the render context belonging to whatever is currently rendered (the page itself or a layout around it).
+struct RenderScope {
+    base_route: String,
+    components: Arc<parking_lot::Mutex<ComponentStore>>,
+
+    new_globals: HashSet<String>,
+    new_runners: Vec<BoxFuture<'static, ()>>,
+    new_routers: Vec<(ComponentRoute, Router)>,
+
+    // renders of dynamic components that are postponed until the rest of the page is sent, only used while streaming
+    deferred: Option<Vec<BoxFuture<'static, Markup>>>,
+}
+impl RenderScope {
+    fn new(base_route: &str, components: Arc<parking_lot::Mutex<ComponentStore>>) -> Self {
+        Self {
+            base_route: base_route.to_string(),
+            components,
+
+            new_globals: HashSet::new(),
             new_runners: Vec::new(),
             new_routers: Vec::new(),
-            new_globals: HashSet::new(),
+
+            deferred: None,
         }
     }
 
-    fn finish(self) -> RenderResult {
+    fn finish(&mut self) -> RenderResult {
         RenderResult {
-            runners: self.new_runners,
-            routers: self.new_routers,
-            new_components: self.new_globals,
+            runners: std::mem::take(&mut self.new_runners),
+            routers: std::mem::take(&mut self.new_routers),
+            new_components: std::mem::take(&mut self.new_globals),
         }
     }
-
-    fn notify_global(&mut self, id: &str) {
-        self.new_globals.insert(id.to_string());
-    }
 }
 
 /// The result of a page render.
@@ -147,39 +206,336 @@ impl RenderContext {
 /// * `scripts` - A list of scripts that should be included in the page.
 /// * `runners` - A list of runners that should be executed.
 /// * `routers` - A list of routers that should be accessible from the page at the given routes
+#[derive(Default)]
 pub struct RenderResult {
     pub runners: Vec<BoxFuture<'static, ()>>,
     pub routers: Vec<(ComponentRoute, Router)>,
     pub new_components: HashSet<String>,
 }
 
-/// Enter a page render context.
+impl RenderResult {
+    fn extend(&mut self, other: RenderResult) {
+        self.runners.extend(other.runners);
+        self.routers.extend(other.routers);
+        self.new_components.extend(other.new_components);
+    }
+}
+
+/// Render a page within its own render context.
+///
+/// The render context is only visible to the task awaiting the returned future, so any number of
+/// renders can happen at the same time.
+/// If anything during the render [failed](fail_render), the first error is returned instead of the rendered markup.
+/// * `base_route` - The route under which the api endpoints of newly built components are placed.
+/// * `components` - The store used for looking up and caching the built components.
+/// * `request` - The request the page is rendered for, if any. It is made available through [`request`].
+/// * `render` - The future rendering the page contents.
+///
+/// Returns the rendered markup alongside the [`RenderResult`] containing all the scripts, runners and routers that were collected during the rendering.
+/// The result is returned even if the render failed, since the components that were built successfully are kept.
+pub async fn render_page<F>(
+    base_route: &str,
+    components: Arc<parking_lot::Mutex<ComponentStore>>,
+    request: Option<RequestContext>,
+    render: F,
+) -> (Result<Markup, RenderError>, RenderResult)
+where
+    F: Future<Output = Result<Markup, RenderError>>,
+{
+    let context = RenderContext::new(base_route, components, request);
+    let (render, result, _) = render_in_context(context, render).await;
+    (render, result)
+}
+
+/// Render a page within its own render context, postponing the renders of dynamic components.
+///
+/// This works like [`render_page`], except that dynamic components that were already built are not rendered right away.
+/// Instead, an empty placeholder is put into the page and the render is added to the returned [`DeferredRenders`].
+/// This allows sending the page before slow components finish rendering.
+pub async fn render_page_streaming<F>(
+    base_route: &str,
+    components: Arc<parking_lot::Mutex<ComponentStore>>,
+    request: Option<RequestContext>,
+    render: F,
+) -> (Result<Markup, RenderError>, RenderResult, DeferredRenders)
+where
+    F: Future<Output = Result<Markup, RenderError>>,
+{
+    let mut context = RenderContext::new(base_route, components, request);
+    context.scope.deferred = Some(Vec::new());
+
+    let (render, result, mut context) = render_in_context(context, render).await;
+    let renders = context.scope.deferred.take().unwrap_or_default();
+
+    (render, result, DeferredRenders { context, renders })
+}
+
+async fn render_in_context<F>(
+    context: RenderContext,
+    render: F,
+) -> (Result<Markup, RenderError>, RenderResult, RenderContext)
+where
+    F: Future<Output = Result<Markup, RenderError>>,
+{
+    RENDER_CONTEXT
+        .scope(RefCell::new(context), async move {
+            let render = render.await;
+            RENDER_CONTEXT.with(|context| {
+                let mut context = context.borrow_mut();
+                let render = match context.error.take() {
+                    // errors of components happened before the body finished, so they take priority
+                    Some(error) => Err(error),
+                    None => render,
+                };
+                let result = context.scope.finish();
+
+                // keep the context around for deferred renders
+                let empty = RenderContext::new("", Arc::default(), None);
+                (render, result, std::mem::replace(&mut *context, empty))
+            })
+        })
+        .await
+}
+
+/// Renders of dynamic components that were postponed by [`render_page_streaming`].
+pub struct DeferredRenders {
+    context: RenderContext,
+    renders: Vec<BoxFuture<'static, Markup>>,
+}
+
+impl DeferredRenders {
+    /// The amount of postponed renders.
+    pub fn len(&self) -> usize {
+        self.renders.len()
+    }
+
+    /// Whether there are no postponed renders.
+    pub fn is_empty(&self) -> bool {
+        self.renders.is_empty()
+    }
+
+    /// Run all the postponed renders concurrently.
+    ///
+    /// `on_render` is called with the id of the placeholder and the rendered markup as soon as a render finishes.
+    /// Every render gets its own render context sharing the request and components of the page, so renders that are in
+    /// progress at the same time don't interfere with each other. Components within the postponed renders are rendered right away.
+    ///
+    /// Since the page has already been sent at this point, errors can't change the response anymore. They are logged and the component is left empty.
+    pub async fn render<F>(self, mut on_render: F) -> RenderResult
+    where
+        F: FnMut(usize, Markup),
+    {
+        let Self { context, renders } = self;
+        let RenderContext { request, scope, .. } = context;
+
+        let mut renders: FuturesUnordered<_> = renders
+            .into_iter()
+            .enumerate()
+            .map(|(id, render)| {
+                let context = RenderContext::new(
+                    &scope.base_route,
+                    scope.components.clone(),
+                    request.clone(),
+                );
+                RENDER_CONTEXT.scope(RefCell::new(context), async move {
+                    let render = render.await;
+                    let result = RENDER_CONTEXT.with(|context| {
+                        let mut context = context.borrow_mut();
+                        if let Some(error) = context.error.take() {
+                            error!("streamed component failed to render: {}", error);
+                        }
+                        context.scope.finish()
+                    });
+                    (id, render, result)
+                })
+            })
+            .collect();
+
+        let mut result = RenderResult::default();
+        while let Some((id, render, render_result)) = renders.next().await {
+            on_render(id, render);
+            result.extend(render_result);
+        }
+        result
+    }
+}
+
+/// Mark the current page render as failed.
 ///
-/// This should be called before rendering any components.
-/// After the rendering is complete, `exit_page` should be called to acquire the results.
-/// Calling `enter_page` while another page is being rendered results in the loss of the previous page's render results!
-pub async fn enter_page(page: &mut BuiltPage) {
-    if render_context().lock().is_some() {
-        warn!("tried to render a page while another page is already being rendered");
+/// The page responds with the given error instead of the rendered markup. If the render already failed, the first error is kept.
+/// Components that fail are not cached, so they are built again on the next render.
+///
+/// Fallible page bodies ([`Page::try_with_body`](crate::Page::try_with_body)) and components returning a `Result` call this automatically,
+/// so this is only needed for failing from within functions that can only return [`Markup`].
+/// Outside of a page render, the error is only logged.
+pub fn fail_render(error: impl IntoResponse) {
+    let mut error = Some(RenderError::from_response(error));
+    with_render_context(|context| context.fail(error.take().unwrap()));
+
+    if let Some(error) = error {
+        error!("render failed outside of a page render: {}", error);
     }
+}
+
+/// the number of errors that happened during the current render so far.
+pub(crate) fn error_count() -> usize {
+    with_render_context(|context| context.error_count).unwrap_or(0)
+}
+
+/// Render something within a separate scope of the current render context.
+///
+/// This is used for rendering things that are shared between multiple pages, like [layouts](crate::page::Layout).
+/// Within the scope, components are looked up in and added to `components` and newly built components get their api
+/// routes below `base_route`. Everything collected within the scope is returned instead of being added to the page.
+/// Errors and the request are shared with the page. Components are never [streamed](crate::Page::streaming) within a scope.
+///
+/// If no page is being rendered, `render` is just awaited.
+pub async fn render_scoped<F>(
+    base_route: &str,
+    components: Arc<parking_lot::Mutex<ComponentStore>>,
+    render: F,
+) -> (F::Output, RenderResult)
+where
+    F: Future,
+{
+    let scope = RenderScope::new(base_route, components);
+    let Some(outer) = with_render_context(|context| std::mem::replace(&mut context.scope, scope))
+    else {
+        return (render.await, RenderResult::default());
+    };
+
+    let render = render.await;
+
+    let result = with_render_context(|context| {
+        let mut scope = std::mem::replace(&mut context.scope, outer);
+        scope.finish()
+    })
+    .expect("render context disappeared during a scoped render");
 
-    let new_context = RenderContext::new(&page.api_path, page.components.clone()).await;
+    (render, result)
+}
 
-    render_context().lock().replace(new_context);
+/// the route and component store components are currently built with, if a page is being rendered.
+///
+/// this allows rendering a component again later on (e.g. from one of its routes) using [`render_page`] with the same components.
+pub(crate) fn current_scope() -> Option<(String, Arc<parking_lot::Mutex<ComponentStore>>)> {
+    with_render_context(|context| {
+        (
+            context.scope.base_route.clone(),
+            context.scope.components.clone(),
+        )
+    })
 }
 
-/// Exit a page render context.
+/// Get the request the current page is being rendered for.
+///
+/// This is available from within page bodies and dynamic components. It returns `None` if
+/// * no page is being rendered
+/// * the page is rendered without a request (e.g. the pre-render when building the page or an [export](crate::Website::export))
+/// * a static component is being rendered. Static components are only rendered once and then reused for every visitor, so they
+///   shouldn't depend on the request. Use a [dynamic component](crate::dyn_component) instead.
+/// ```rust
+/// use fishnet::component::prelude::*;
+///
+/// #[dyn_component]
+/// async fn greeting() {
+///     let name = request().and_then(|request| request.query("name"));
+///
+///     html! {
+///         "hello " (name.unwrap_or_else(|| "stranger".to_string())) "!"
+///     }
+/// }
+/// ```
+pub fn request() -> Option<RequestContext> {
+    with_render_context(|context| {
+        if context.temporary_render_depth > 0 {
+            return None;
+        }
+        context.request.clone()
+    })
+    .flatten()
+}
+
+/// Markup passed into a component by the component rendering it.
+///
+/// Slots are passed using the [`c!`](crate::c!) macro and read from within the component using [`slot`] and [`children`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Slots(Vec<(&'static str, String)>);
+
+impl Slots {
+    /// Create an empty set of slots.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fill the slot with the given name.
+    pub fn with(mut self, name: &'static str, markup: Markup) -> Self {
+        self.0.retain(|(slot, _)| *slot != name);
+        self.0.push((name, markup.into_string()));
+        self
+    }
+
+    /// Get the markup of a slot.
+    pub fn get(&self, name: &str) -> Option<Markup> {
+        self.0
+            .iter()
+            .find(|(slot, _)| *slot == name)
+            .map(|(_, markup)| PreEscaped(markup.clone()))
+    }
+}
+
+/// the slots of the component that is currently rendered.
+pub(crate) fn current_slots() -> Slots {
+    SLOTS.try_with(Slots::clone).unwrap_or_default()
+}
+
+/// render with the given slots outside of the component that passes them, e.g. in the render route of a component.
+pub(crate) async fn with_slots<F: Future>(slots: Slots, render: F) -> F::Output {
+    SLOTS.scope(slots, render).await
+}
+
+/// hash of the slots of the component that is currently rendered.
+///
+/// cached renders are only reused for the slots they were rendered with.
+pub(crate) fn slots_hash() -> u64 {
+    SLOTS
+        .try_with(hash_key)
+        .unwrap_or_else(|_| hash_key(&Slots::new()))
+}
+
+/// Get the markup passed into the named slot of the component that is currently rendered.
+///
+/// Returns empty markup if the slot wasn't filled or no component is being rendered.
+/// ```rust
+/// use fishnet::component::prelude::*;
+///
+/// #[component]
+/// async fn card() {
+///     html! {
+///         div.header { (slot("header")) }
+///         div.body { (children()) }
+///     }
+/// }
 ///
-/// This should be called after rendering all components. It will return the `RenderResult` containing all the scripts and runners that were collected during the rendering.
-/// # Panics
-/// Panics if no page is currently being rendered. (i.e. `enter_page` was not called before)
-pub async fn exit_page() -> RenderResult {
-    let mut context = render_context().lock();
+/// async fn render() -> Markup {
+///     html! {
+///         (c!(card(), header = html! { "fishnet" }, children = html! { "a web framework" }))
+///     }
+/// }
+/// ```
+pub fn slot(name: &str) -> Markup {
+    SLOTS
+        .try_with(|slots| slots.get(name))
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+}
 
-    context
-        .take()
-        .expect("tried to exit a page while no page is being rendered")
-        .finish()
+/// Get the markup passed into the default slot (`children`) of the component that is currently rendered.
+///
+/// See [`slot`].
+pub fn children() -> Markup {
+    slot("children")
 }
 
 /// Render a component into the current page render context.
@@ -195,47 +551,142 @@ where
     F: FnOnce() -> C,
     C: BuildableComponent,
 {
-    let is_temporary;
-    let existing_component;
-    let base_route;
-    {
-        let mut context_guard = render_context().lock();
-        if context_guard.is_none() {
-            error!(
-                context_id,
-                "tried to add a component while no page is being rendered"
-            );
-            #[cfg(debug_assertions)]
-            {
-                return html! { "rendering failed for context " (context_id) ": no page is being rendered" };
-            }
-            #[cfg(not(debug_assertions))]
-            {
-                return html! {};
-            }
-        }
+    render_component_instance(context_id, None, Slots::new(), lazy_component).await
+}
 
-        let context = context_guard.as_mut().unwrap();
+/// Render one of multiple instances of a component into the current page render context.
+///
+/// This works like [`render_component`], but every distinct `key` gets its own component with its own state and api route.
+/// Use this when rendering components in a loop, otherwise every iteration would reuse the component of the first one.
+/// It is highly recommended to use the keyed form of the [`c!`](crate::c!) macro instead of calling this function directly.
+pub async fn render_keyed_component<K, F, C>(
+    context_id: &'static str,
+    key: &K,
+    lazy_component: F,
+) -> Markup
+where
+    K: Hash + ?Sized,
+    F: FnOnce() -> C,
+    C: BuildableComponent,
+{
+    render_component_instance(
+        context_id,
+        Some(hash_key(key)),
+        Slots::new(),
+        lazy_component,
+    )
+    .await
+}
 
-        is_temporary = context.temporary_render_depth > 0;
-        existing_component = context.components.0.get(context_id).cloned();
-        base_route = context.base_route.clone();
-    }
+/// Render a component into the current page render context, passing markup into its [`Slots`].
+///
+/// This works like [`render_component`]. The slots are evaluated on every render, while the component is only built once.
+/// It is highly recommended to use the [`c!`](crate::c!) macro with slots instead of calling this function directly.
+pub async fn render_component_with_slots<F, C>(
+    context_id: &'static str,
+    slots: Slots,
+    lazy_component: F,
+) -> Markup
+where
+    F: FnOnce() -> C,
+    C: BuildableComponent,
+{
+    render_component_instance(context_id, None, slots, lazy_component).await
+}
 
-    let render;
-    if existing_component.is_some() {
-        let existing_component = existing_component.unwrap();
+/// Render one of multiple instances of a component into the current page render context, passing markup into its [`Slots`].
+///
+/// See [`render_keyed_component`] and [`render_component_with_slots`].
+pub async fn render_keyed_component_with_slots<K, F, C>(
+    context_id: &'static str,
+    key: &K,
+    slots: Slots,
+    lazy_component: F,
+) -> Markup
+where
+    K: Hash + ?Sized,
+    F: FnOnce() -> C,
+    C: BuildableComponent,
+{
+    render_component_instance(context_id, Some(hash_key(key)), slots, lazy_component).await
+}
+
+fn hash_key<K: Hash + ?Sized>(key: &K) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+async fn render_component_instance<F, C>(
+    context_id: &'static str,
+    key: Option<u64>,
+    slots: Slots,
+    lazy_component: F,
+) -> Markup
+where
+    F: FnOnce() -> C,
+    C: BuildableComponent,
+{
+    let store_key = (context_id, key);
+    let context = with_render_context(|context| {
+        (
+            context.temporary_render_depth > 0,
+            context.scope.components.lock().0.get(&store_key).cloned(),
+            context.scope.base_route.clone(),
+        )
+    });
+    let Some((is_temporary, existing_component, base_route)) = context else {
+        error!(
+            context_id,
+            "tried to add a component while no page is being rendered"
+        );
+        #[cfg(debug_assertions)]
+        {
+            return html! { "rendering failed for context " (context_id) ": no page is being rendered" };
+        }
+        #[cfg(not(debug_assertions))]
+        {
+            return html! {};
+        }
+    };
 
+    let errors = error_count();
+    let render;
+    if let Some(existing_component) = existing_component {
+        existing_component.update_slots(&slots);
         // IMPORTANT: Since may lead to recursive calls, all the locks need to be dropped before calling
         if is_temporary {
-            render = existing_component.render_if_static().unwrap_or_default();
+            if existing_component.is_dynamic() {
+                with_render_context(|context| context.static_state = false);
+                render = html! {};
+            } else {
+                render = SLOTS.scope(slots, existing_component.render()).await;
+            }
+            // linked after rendering, since a stale component gets a new expiry while rendering
+            link_static_parent(&existing_component);
+        } else if let Some(placeholder) = defer_render(&existing_component, &slots) {
+            render = placeholder;
         } else {
-            render = existing_component.render().await;
+            render = SLOTS.scope(slots, existing_component.render()).await;
         }
     } else {
         // IMPORTANT: Since may lead to recursive calls, all the locks need to be dropped before calling
         trace!("building component");
-        let new_component = lazy_component().build(&base_route).await;
+        let new_component = SLOTS
+            .scope(
+                slots.clone(),
+                lazy_component().build_instance(&base_route, context_id, key),
+            )
+            .await;
+        if error_count() > errors {
+            // the pre-render of a static component already failed and reported the error for this render,
+            // so it isn't rendered a second time
+            debug!(
+                context_id,
+                "component failed while being built, discarding it"
+            );
+            return html! {};
+        }
         trace!("rendering component");
         if is_temporary {
             render = new_component
@@ -243,42 +694,83 @@ where
                 .render_if_static()
                 .unwrap_or_default();
         } else {
-            render = new_component.built_component.render().await;
+            render = SLOTS
+                .scope(slots, new_component.built_component.render())
+                .await;
         }
 
-        {
-            let mut context_guard = render_context().lock();
-            if context_guard.is_none() {
-                error!(
-                    context_id,
-                    "page render exited while a component was still being rendered"
-                );
-                return html! { "rendering failed for context " (context_id) ": page render exited" };
-            }
-
-            let context = context_guard.as_mut().unwrap();
+        if error_count() > errors {
+            // don't keep failed components around, so the next render can try again
+            debug!(context_id, "component failed to render, discarding it");
+            return render;
+        }
+        link_static_parent(&new_component.built_component);
 
+        let stored = with_render_context(|context| {
             context.static_state &= !new_component.built_component.is_dynamic();
 
+            if !context.temporary_render_depth > 0 {
+                // another render might have built the same component in the meantime.
+                // in that case the already stored component (and its routes/runner) wins.
+                match context.scope.components.lock().0.entry(store_key) {
+                    Entry::Occupied(_) => return,
+                    Entry::Vacant(entry) => {
+                        entry.insert(Arc::new(new_component.built_component));
+                    }
+                }
+            }
+
             if let Some(router) = new_component.router {
-                context.new_routers.push(router)
+                context.scope.new_routers.push(router)
             }
             if let Some(runner) = new_component.runner {
-                context.new_runners.push(runner);
+                context.scope.new_runners.push(runner);
             }
+        });
 
-            if !context.temporary_render_depth > 0 {
-                context
-                    .components
-                    .0
-                    .insert(context_id, Arc::new(new_component.built_component));
-            }
+        if stored.is_none() {
+            error!(
+                context_id,
+                "page render exited while a component was still being rendered"
+            );
+            return html! { "rendering failed for context " (context_id) ": page render exited" };
         }
     }
 
     render
 }
 
+/// make the static component that is currently being pre-rendered (if any) a parent of `component`.
+///
+/// the parent's cached render contains the component, so invalidating the component has to invalidate the parent as well.
+fn link_static_parent(component: &BuiltComponent) {
+    with_render_context(|context| {
+        if let Some(parent) = context.static_parents.last() {
+            component.invalidation().add_parent(parent);
+        }
+    });
+}
+
+/// postpone the render of a dynamic component if the page is streamed.
+///
+/// returns the placeholder that is replaced by the component once it is rendered.
+fn defer_render(component: &Arc<BuiltComponent>, slots: &Slots) -> Option<Markup> {
+    if !component.is_dynamic() {
+        return None;
+    }
+
+    with_render_context(|context| {
+        let deferred = context.scope.deferred.as_mut()?;
+        let component = component.clone();
+        let slots = slots.clone();
+
+        let id = deferred.len();
+        deferred.push(async move { SLOTS.scope(slots, component.render()).await }.boxed());
+        Some(stream::placeholder(id))
+    })
+    .flatten()
+}
+
 /// Enter a temporary render context.
 ///
 /// While in a temporary render context, rendered components will not be saved to the page's component store.
@@ -290,14 +782,13 @@ where
 ///
 /// You usually don't need to call this function yourself.
 pub async fn enter_temporary_render() {
-    let mut context = render_context().lock();
-    if let Some(context) = context.as_mut() {
+    with_render_context(|context| {
         trace!("entering temporary render");
         if context.temporary_render_depth == 0 {
             context.static_state = true;
         }
         context.temporary_render_depth += 1;
-    }
+    });
 }
 
 /// Exit a temporary render context.
@@ -307,8 +798,7 @@ pub async fn enter_temporary_render() {
 ///
 /// You usually don't need to call this function yourself.
 pub async fn exit_temporary_render() -> bool {
-    let mut context = render_context().lock();
-    if let Some(context) = context.as_mut() {
+    with_render_context(|context| {
         if context.temporary_render_depth == 0 {
             warn!("tried to exit temporary render while not in temporary render");
             return true;
@@ -320,9 +810,22 @@ pub async fn exit_temporary_render() -> bool {
         );
         context.temporary_render_depth -= 1;
         context.static_state
-    } else {
-        true
-    }
+    })
+    .unwrap_or(true)
+}
+
+/// pre-render a static component, see [`enter_temporary_render`].
+///
+/// components rendered until [`exit_static_render`] is called are linked to the invalidation of the static component.
+pub(crate) async fn enter_static_render(invalidation: &Invalidation) {
+    with_render_context(|context| context.static_parents.push(invalidation.clone()));
+    enter_temporary_render().await;
+}
+
+/// finish the pre-render of a static component started using [`enter_static_render`].
+pub(crate) async fn exit_static_render() -> bool {
+    with_render_context(|context| context.static_parents.pop());
+    exit_temporary_render().await
 }
 
 /// add [`css`](crate::css!) to the page
@@ -475,12 +978,110 @@ macro_rules! script_external {
 /// }.boxed());
 /// ```
 ///
+/// # rendering lists
+/// every `c!` refers to a single component, so a `c!` within a loop would render the same component for every item.
+/// by giving the component a key, every distinct key gets its own instance of the component with its own state and api route:
+/// ```rust
+/// use fishnet::{
+///     Page,
+///     component::prelude::*
+/// };
+///
+/// #[component]
+/// async fn todo_item(id: usize) {
+///     let state = state_init!(id);
+///
+///     html! {
+///         button hx-post=(state.endpoint()) { "todo " (*state) }
+///     }
+/// }
+///
+/// Page::new("todos").with_body(|| async {
+///     html! {
+///         @for id in 0..3 {
+///             (c!(key = id, todo_item(id)))
+///         }
+///     }
+/// }.boxed());
+/// ```
+/// the key can be anything that implements [`Hash`](std::hash::Hash).
+///
+/// # slots
+/// markup can be passed into a component after the component itself, either into the default slot (`children`) or into named
+/// slots. the component reads it using [`children`] and [`slot`]:
+/// ```rust
+/// use fishnet::{
+///     Page,
+///     component::prelude::*
+/// };
+///
+/// #[component]
+/// async fn card() {
+///     html! {
+///         section.card {
+///             h2 { (slot("title")) }
+///             (children())
+///         }
+///     }
+/// }
+///
+/// Page::new("cards").with_body(|| async {
+///     html! {
+///         (c!(card(), html! { p { "only the default slot" } }))
+///         (c!(card(), title = html! { "named slots" }, children = html! { p { "both at once" } }))
+///     }
+/// }.boxed());
+/// ```
+/// unlike the component itself, the slots are evaluated on every render, so they can contain other components.
+/// a component with slots stays cached as long as the markup of its slots doesn't change.
+///
 /// # calling from outside a page render
 /// you have to call this from within a page render or it will not work.
 /// if you are in debug mode it will render out an error message containing the associated context id.
 /// in release mode it will just render to nothing.
 #[macro_export]
 macro_rules! c {
+    (key = $key:expr, $component:expr, $($slot:ident = $markup:expr),+ $(,)?) => {{
+        let component = || $component;
+        let slots = $crate::page::render_context::Slots::new()
+            $(.with(stringify!($slot), $markup))+;
+
+        $crate::page::render_context::render_keyed_component_with_slots(
+            $crate::const_nanoid!(10),
+            &$key,
+            slots,
+            component,
+        )
+        .await
+    }};
+    (key = $key:expr, $component:expr, $children:expr) => {
+        $crate::c!(key = $key, $component, children = $children)
+    };
+    (key = $key:expr, $component:expr) => {{
+        let component = || $component;
+
+        $crate::page::render_context::render_keyed_component(
+            $crate::const_nanoid!(10),
+            &$key,
+            component,
+        )
+        .await
+    }};
+    ($component:expr, $($slot:ident = $markup:expr),+ $(,)?) => {{
+        let component = || $component;
+        let slots = $crate::page::render_context::Slots::new()
+            $(.with(stringify!($slot), $markup))+;
+
+        $crate::page::render_context::render_component_with_slots(
+            $crate::const_nanoid!(10),
+            slots,
+            component,
+        )
+        .await
+    }};
+    ($component:expr, $children:expr) => {
+        $crate::c!($component, children = $children)
+    };
     ($component:expr) => {{
         let component = || $component;
 
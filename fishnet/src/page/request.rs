@@ -0,0 +1,217 @@
+//! information about the request a page is being rendered for.
+
+use axum::http::{request::Parts, HeaderMap, Method, StatusCode, Uri};
+use std::sync::Arc;
+
+use super::PathParams;
+use crate::auth::AuthUser;
+use crate::session::Session;
+use crate::website::CsrfToken;
+
+/// the request that caused the current page render.
+///
+/// use [`render_context::request`](crate::page::render_context::request) to access it from within a page body or a dynamic component.
+#[derive(Debug, Clone)]
+pub struct RequestContext {
+    parts: Arc<Parts>,
+    params: PathParams,
+    error_status: Option<StatusCode>,
+}
+
+impl RequestContext {
+    /// create a new request context from the parts of a request and the parameters of the page path.
+    pub fn new(parts: Parts, params: PathParams) -> Self {
+        Self {
+            parts: Arc::new(parts),
+            params,
+            error_status: None,
+        }
+    }
+
+    /// mark the request as failed with the given status.
+    pub(crate) fn with_error_status(mut self, status: StatusCode) -> Self {
+        self.error_status = Some(status);
+        self
+    }
+
+    /// the status of the failed request if an [error page](crate::Website::error_page) is being rendered.
+    pub fn error_status(&self) -> Option<StatusCode> {
+        self.error_status
+    }
+
+    /// the request method.
+    pub fn method(&self) -> &Method {
+        &self.parts.method
+    }
+
+    /// the full uri of the request.
+    pub fn uri(&self) -> &Uri {
+        &self.parts.uri
+    }
+
+    /// the path of the request.
+    pub fn path(&self) -> &str {
+        self.parts.uri.path()
+    }
+
+    /// the parameters of the page path (see [`Page::with_param_body`](crate::Page::with_param_body)).
+    pub fn params(&self) -> &PathParams {
+        &self.params
+    }
+
+    /// all the headers of the request.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.parts.headers
+    }
+
+    /// get the value of a single header.
+    ///
+    /// returns `None` if the header is missing or is not valid utf-8.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.parts
+            .headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+    }
+
+    /// all the `(name, value)` pairs of the query string, in order.
+    pub fn query_pairs(&self) -> Vec<(String, String)> {
+        match self.parts.uri.query() {
+            Some(query) => form_urlencoded::parse(query.as_bytes())
+                .into_owned()
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// get the first value of a query parameter.
+    pub fn query(&self, name: &str) -> Option<String> {
+        let query = self.parts.uri.query()?;
+        form_urlencoded::parse(query.as_bytes())
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+    }
+
+    /// get the value of a cookie.
+    pub fn cookie(&self, name: &str) -> Option<&str> {
+        self.parts
+            .headers
+            .get_all(axum::http::header::COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|cookies| cookies.split(';'))
+            .filter_map(|cookie| cookie.trim().split_once('='))
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value.trim_matches('"'))
+    }
+
+    /// the csrf token of the client, if [csrf protection](crate::Website::csrf_protection) is enabled.
+    ///
+    /// requests sent by htmx include the token automatically, other requests changing data have to send it in the
+    /// [`CSRF_HEADER`](crate::website::CSRF_HEADER).
+    pub fn csrf_token(&self) -> Option<&CsrfToken> {
+        self.parts.extensions.get::<CsrfToken>()
+    }
+
+    /// the session of the visitor, if [sessions](crate::Website::sessions) are enabled.
+    pub fn session(&self) -> Option<&Session> {
+        self.parts.extensions.get::<Session>()
+    }
+
+    /// the user authenticated by the guard of the page, if it [requires authentication](crate::Page::require_auth).
+    pub fn user(&self) -> Option<&str> {
+        self.parts
+            .extensions
+            .get::<AuthUser>()
+            .map(|AuthUser(user)| user.as_str())
+    }
+
+    /// the languages accepted by the client, ordered from most to least preferred.
+    ///
+    /// this is parsed from the `Accept-Language` header.
+    pub fn languages(&self) -> Vec<&str> {
+        let Some(header) = self.header("accept-language") else {
+            return Vec::new();
+        };
+
+        let mut languages: Vec<(&str, f32)> = header
+            .split(',')
+            .filter_map(|language| {
+                let mut parts = language.split(';');
+                let tag = parts.next()?.trim();
+                if tag.is_empty() {
+                    return None;
+                }
+
+                let quality = parts
+                    .find_map(|part| part.trim().strip_prefix("q="))
+                    .and_then(|quality| quality.parse().ok())
+                    .unwrap_or(1.0);
+                Some((tag, quality))
+            })
+            .filter(|(_, quality)| *quality > 0.0)
+            .collect();
+
+        // stable sort, so languages with the same quality keep their order
+        languages.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        languages.into_iter().map(|(tag, _)| tag).collect()
+    }
+
+    /// the most preferred language of the client that is contained in `supported`.
+    ///
+    /// languages are compared by their primary tag as well, so `en-US` will match `en`.
+    pub fn preferred_language<'a>(&self, supported: &[&'a str]) -> Option<&'a str> {
+        self.languages().into_iter().find_map(|language| {
+            let primary = language.split('-').next().unwrap_or(language);
+            supported
+                .iter()
+                .find(|supported| {
+                    supported.eq_ignore_ascii_case(language)
+                        || supported.eq_ignore_ascii_case(primary)
+                })
+                .copied()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Request;
+
+    fn context(uri: &str, headers: &[(&str, &str)]) -> RequestContext {
+        let mut request = Request::builder().uri(uri);
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        let (parts, _) = request.body(()).unwrap().into_parts();
+
+        RequestContext::new(parts, PathParams::default())
+    }
+
+    #[test]
+    fn test_query() {
+        let request = context("/page?name=fish%20net&page=2&page=3", &[]);
+        assert_eq!(request.path(), "/page");
+        assert_eq!(request.query("name").as_deref(), Some("fish net"));
+        assert_eq!(request.query("page").as_deref(), Some("2"));
+        assert_eq!(request.query("missing"), None);
+        assert_eq!(request.query_pairs().len(), 3);
+    }
+
+    #[test]
+    fn test_cookie() {
+        let request = context("/", &[("cookie", "theme=dark; session=\"abc\"")]);
+        assert_eq!(request.cookie("theme"), Some("dark"));
+        assert_eq!(request.cookie("session"), Some("abc"));
+        assert_eq!(request.cookie("missing"), None);
+    }
+
+    #[test]
+    fn test_languages() {
+        let request = context("/", &[("accept-language", "de;q=0.8, en-US, fr;q=0")]);
+        assert_eq!(request.languages(), vec!["en-US", "de"]);
+        assert_eq!(request.preferred_language(&["de", "en"]), Some("en"));
+        assert_eq!(request.preferred_language(&["fr"]), None);
+    }
+}
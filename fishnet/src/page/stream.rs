@@ -0,0 +1,32 @@
+//! markup used for streaming pages (see [`Page::streaming`](crate::Page::streaming)).
+//!
+//! components that are rendered after the rest of the page leave an empty placeholder in the page.
+//! once they finish, their markup is sent inside of a template, followed by a script that swaps the
+//! placeholder with the contents of the template.
+
+use maud::{html, Markup, PreEscaped};
+
+// sent in front of the page body, so it is available before the first component arrives
+const SWAP_SCRIPT: &str = "function fishnetSwap(id){\
+const t=document.querySelector('template[data-fishnet-stream=\"'+id+'\"]');\
+document.getElementById('fishnet-stream-'+id).replaceWith(t.content);\
+t.remove();document.currentScript.remove();}";
+
+pub(crate) fn swap_script() -> Markup {
+    html! {
+        script { (PreEscaped(SWAP_SCRIPT)) }
+    }
+}
+
+pub(crate) fn placeholder(id: usize) -> Markup {
+    html! {
+        template id=(format!("fishnet-stream-{}", id)) {}
+    }
+}
+
+pub(crate) fn chunk(id: usize, render: Markup) -> Markup {
+    html! {
+        template data-fishnet-stream=(id) { (render) }
+        script { (PreEscaped(format!("fishnetSwap({})", id))) }
+    }
+}
@@ -1,16 +1,30 @@
 use axum::{
     extract::{Path, Request},
     http::StatusCode,
-    response::IntoResponse,
+    response::{IntoResponse, Response},
     routing::any,
     Extension, Router,
 };
 use tower_service::Service;
 
+use crate::page::{params::fill_path, PathParams};
+
 use hashbrown::HashMap;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+/// marks a response as being caused by a path that doesn't exist.
+///
+/// the website uses this to tell apart "real" not found responses from ones that were intentionally returned by a route.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RouteNotFound;
+
+pub(crate) fn not_found(message: &'static str) -> Response {
+    let mut response = (StatusCode::NOT_FOUND, message).into_response();
+    response.extensions_mut().insert(RouteNotFound);
+    response
+}
+
 #[derive(Debug, Clone)]
 pub struct ComponentRoute {
     full: Arc<String>,
@@ -57,7 +71,7 @@ impl APIRouter {
     }
 
     pub async fn add_component(
-        &mut self,
+        &self,
         component_route: ComponentRoute,
         component_router: Router<()>,
     ) {
@@ -65,35 +79,62 @@ impl APIRouter {
 
         inner
             .routes
-            .insert(component_route.component_only_string(), component_router);
+            .insert(component_route.to_string(), component_router);
+    }
+
+    /// the full routes of all the components added to the router.
+    pub async fn routes(&self) -> Vec<String> {
+        self.0.lock().await.routes.keys().cloned().collect()
     }
 
     async fn get(
         Extension(router): Extension<APIRouter>,
-        Path(mut component_route): Path<String>,
+        Path(params): Path<Vec<(String, String)>>,
         mut req: Request,
     ) -> impl IntoResponse {
-        let mut inner = router.0.lock().await;
-        if let Some((c, _)) = component_route.split_once('/') {
-            component_route = c.to_string();
-        }
-        let full_route = format!("{}/{}", inner.base_route, component_route);
+        let params = PathParams::new(params);
+        let component_route = params.get("component_route").unwrap_or_default();
+        let component = match component_route.split_once('/') {
+            Some((component, _)) => component,
+            None => component_route,
+        };
+
+        // don't hold the lock while the component handles the request, routers are cheap to clone.
+        let router = {
+            let inner = router.0.lock().await;
+            // the base route may contain the parameters of a pattern page
+            let full_route = format!("{}/{}", fill_path(&inner.base_route, &params), component);
+            inner.routes.get(&full_route).cloned()
+        };
 
-        if let Some(router) = inner.routes.get_mut(&component_route) {
-            // Strip the component route from the request path.
-            // TODO: im not that happy with this code
-            let uri = format!("{}/", req.uri());
-            *req.uri_mut() = uri
-                .replace(&full_route, "")
-                .parse()
-                .expect("failed to parse uri");
+        if let Some(mut router) = router {
+            // Strip everything up to the component from the request path.
+            let path = req.uri().path();
+            let stripped = path
+                .find(&format!("/{}", component))
+                .map(|start| &path[start + component.len() + 1..])
+                .unwrap_or_default();
+            let stripped = if stripped.is_empty() { "/" } else { stripped };
+            let uri = match req.uri().query() {
+                Some(query) => format!("{}?{}", stripped, query),
+                None => stripped.to_string(),
+            };
+            *req.uri_mut() = uri.parse().expect("failed to parse uri");
+            // the parameters of the page, so components can still read them while handling the request
+            let page_params = PathParams::new(
+                params
+                    .iter()
+                    .filter(|(name, _)| *name != "component_route")
+                    .map(|(name, value)| (name.to_string(), value.to_string())),
+            );
+            req.extensions_mut().insert(page_params);
 
             let res = router.call(req).await;
             res.unwrap_or_else(|_| {
                 (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error").into_response()
             })
         } else {
-            (StatusCode::NOT_FOUND, "API route does not exist").into_response()
+            not_found("API route does not exist")
         }
     }
 
@@ -0,0 +1,518 @@
+//! per-visitor sessions, stored server side and identified by a cookie.
+//!
+//! sessions are enabled using [`Website::sessions`](crate::Website::sessions), which takes the [`SessionStore`] the data is
+//! kept in. fishnet comes with a [`MemoryStore`] and a [`FileStore`], other backends can implement the trait themselves.
+//!
+//! the [`Session`] of the current visitor can be used as an extractor in component routes and read from dynamic component
+//! renders using [`RequestContext::session`](crate::page::RequestContext::session):
+//! ```rust
+//! use fishnet::component::prelude::*;
+//! use fishnet::session::Session;
+//!
+//! #[dyn_component]
+//! async fn theme_toggle() {
+//!     let state = state!(());
+//!
+//!     #[route("/", POST)]
+//!     async fn toggle(session: Session) -> Markup {
+//!         let dark = session.get("theme").as_deref() != Some("dark");
+//!         session.insert("theme", if dark { "dark" } else { "light" });
+//!         html! { "switched to the " (if dark { "dark" } else { "light" }) " theme" }
+//!     }
+//!
+//!     let theme = request()
+//!         .and_then(|request| request.session()?.get("theme"))
+//!         .unwrap_or_else(|| "light".to_string());
+//!
+//!     html! {
+//!         button hx-post=(state.endpoint()) hx-swap="innerHTML" { "theme: " (theme) }
+//!     }
+//! }
+//! ```
+//! sessions that weren't used for a while expire, see [`MemoryStore::idle_timeout`] and [`FileStore::idle_timeout`].
+//! the cookie is kept until the browser is closed, unless a lifetime is set using
+//! [`Website::session_lifetime`](crate::Website::session_lifetime).
+//!
+//! sessions are only saved (and the cookie only set) once something was inserted. changes are saved after the handler
+//! returned, so changes made by [streamed](crate::Page::streaming) components are only kept if the streamed part of the page
+//! is rendered before the response finished.
+
+use async_trait::async_trait;
+use axum::{
+    extract::{FromRequestParts, Request, State},
+    http::{header, request::Parts, HeaderValue, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use hashbrown::HashMap;
+use rand::RngCore;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::time::Instant;
+use tracing::{debug, warn};
+
+/// the name of the cookie containing the session id.
+pub const SESSION_COOKIE: &str = "fishnet-session";
+
+const ID_BYTES: usize = 32;
+
+/// how long sessions are kept without being used, unless the store is configured otherwise.
+pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+// expired sessions are removed at most this often, in addition to when they are loaded.
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// the data of a single session.
+pub type SessionData = HashMap<String, String>;
+
+/// a backend storing the data of sessions.
+#[async_trait]
+pub trait SessionStore: Send + Sync + 'static {
+    /// load the data of a session, `None` if the session doesn't exist.
+    async fn load(&self, id: &str) -> Option<SessionData>;
+
+    /// store the data of a session, replacing the previous data.
+    async fn save(&self, id: &str, data: &SessionData);
+
+    /// delete a session.
+    async fn remove(&self, id: &str);
+}
+
+/// a [`SessionStore`] keeping all sessions in memory.
+///
+/// sessions are lost when the website is restarted.
+#[derive(Debug)]
+pub struct MemoryStore {
+    sessions: parking_lot::Mutex<MemorySessions>,
+    idle_timeout: Duration,
+}
+
+#[derive(Debug)]
+struct MemorySessions {
+    // the data of every session and when it was last used
+    data: HashMap<String, (SessionData, Instant)>,
+    last_cleanup: Instant,
+}
+
+impl MemoryStore {
+    /// create a new empty store.
+    pub fn new() -> Self {
+        Self {
+            sessions: parking_lot::Mutex::new(MemorySessions {
+                data: HashMap::new(),
+                last_cleanup: Instant::now(),
+            }),
+            idle_timeout: DEFAULT_IDLE_TIMEOUT,
+        }
+    }
+
+    /// remove sessions that weren't loaded or saved for the given time ([`DEFAULT_IDLE_TIMEOUT`] by default).
+    pub fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = timeout;
+        self
+    }
+
+    fn cleanup(&self, sessions: &mut MemorySessions) {
+        let now = Instant::now();
+        if now.duration_since(sessions.last_cleanup) < CLEANUP_INTERVAL {
+            return;
+        }
+
+        sessions.last_cleanup = now;
+        let before = sessions.data.len();
+        sessions
+            .data
+            .retain(|_, (_, used)| now.duration_since(*used) < self.idle_timeout);
+        debug!("removed {} expired sessions", before - sessions.data.len());
+    }
+}
+
+impl Default for MemoryStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl SessionStore for MemoryStore {
+    async fn load(&self, id: &str) -> Option<SessionData> {
+        let mut sessions = self.sessions.lock();
+        self.cleanup(&mut sessions);
+
+        let (data, used) = sessions.data.get_mut(id)?;
+        let now = Instant::now();
+        if now.duration_since(*used) >= self.idle_timeout {
+            sessions.data.remove(id);
+            return None;
+        }
+        *used = now;
+        Some(data.clone())
+    }
+
+    async fn save(&self, id: &str, data: &SessionData) {
+        let mut sessions = self.sessions.lock();
+        self.cleanup(&mut sessions);
+        sessions
+            .data
+            .insert(id.to_string(), (data.clone(), Instant::now()));
+    }
+
+    async fn remove(&self, id: &str) {
+        self.sessions.lock().data.remove(id);
+    }
+}
+
+/// a [`SessionStore`] keeping every session in its own file within a directory.
+///
+/// the directory is created if it doesn't exist yet. the modification time of a file is the last time the session was used.
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    dir: PathBuf,
+    idle_timeout: Duration,
+    last_cleanup: Arc<parking_lot::Mutex<Instant>>,
+}
+
+impl FileStore {
+    /// create a new store using the given directory.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            idle_timeout: DEFAULT_IDLE_TIMEOUT,
+            last_cleanup: Arc::new(parking_lot::Mutex::new(Instant::now())),
+        }
+    }
+
+    /// remove sessions that weren't loaded or saved for the given time ([`DEFAULT_IDLE_TIMEOUT`] by default).
+    pub fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = timeout;
+        self
+    }
+
+    // ids are validated before they reach the store, so they are safe to use as file names
+    fn path(&self, id: &str) -> PathBuf {
+        self.dir.join(id)
+    }
+
+    fn is_expired(&self, metadata: &std::fs::Metadata) -> bool {
+        metadata
+            .modified()
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .is_some_and(|idle| idle >= self.idle_timeout)
+    }
+
+    async fn cleanup(&self) {
+        {
+            let mut last_cleanup = self.last_cleanup.lock();
+            if last_cleanup.elapsed() < CLEANUP_INTERVAL {
+                return;
+            }
+            *last_cleanup = Instant::now();
+        }
+
+        let Ok(mut entries) = tokio::fs::read_dir(&self.dir).await else {
+            return;
+        };
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            // only touch files that look like sessions
+            if !entry.file_name().to_str().is_some_and(is_valid_id) {
+                continue;
+            }
+            if let Ok(metadata) = entry.metadata().await {
+                if self.is_expired(&metadata) {
+                    let _ = tokio::fs::remove_file(entry.path()).await;
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl SessionStore for FileStore {
+    async fn load(&self, id: &str) -> Option<SessionData> {
+        self.cleanup().await;
+
+        let path = self.path(id);
+        let metadata = tokio::fs::metadata(&path).await.ok()?;
+        if self.is_expired(&metadata) {
+            let _ = tokio::fs::remove_file(&path).await;
+            return None;
+        }
+
+        let data = tokio::fs::read(&path).await.ok()?;
+        // mark the session as used
+        if let Ok(file) = tokio::fs::File::options().write(true).open(&path).await {
+            let _ = file.into_std().await.set_modified(SystemTime::now());
+        }
+        Some(form_urlencoded::parse(&data).into_owned().collect())
+    }
+
+    async fn save(&self, id: &str, data: &SessionData) {
+        let data = form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(data)
+            .finish();
+
+        if let Err(error) = tokio::fs::create_dir_all(&self.dir).await {
+            warn!(
+                "failed to create session directory {:?}: {}",
+                self.dir, error
+            );
+            return;
+        }
+        if let Err(error) = tokio::fs::write(self.path(id), data).await {
+            warn!("failed to save session: {}", error);
+        }
+        self.cleanup().await;
+    }
+
+    async fn remove(&self, id: &str) {
+        let _ = tokio::fs::remove_file(self.path(id)).await;
+    }
+}
+
+/// the session of the current visitor.
+///
+/// sessions are cheap to clone, every clone refers to the same session. the values are plain strings, use
+/// [`get_as`](Session::get_as) for parsing them.
+#[derive(Debug, Clone)]
+pub struct Session(Arc<SessionInner>);
+
+#[derive(Debug)]
+struct SessionInner {
+    id: Option<String>,
+    data: parking_lot::Mutex<SessionData>,
+    changed: AtomicBool,
+}
+
+impl Session {
+    fn new(id: Option<String>, data: SessionData) -> Self {
+        Self(Arc::new(SessionInner {
+            id,
+            data: parking_lot::Mutex::new(data),
+            changed: AtomicBool::new(false),
+        }))
+    }
+
+    /// get a value from the session.
+    pub fn get(&self, key: &str) -> Option<String> {
+        self.0.data.lock().get(key).cloned()
+    }
+
+    /// get a value from the session and parse it, `None` if it is missing or can't be parsed.
+    pub fn get_as<T: FromStr>(&self, key: &str) -> Option<T> {
+        self.0.data.lock().get(key)?.parse().ok()
+    }
+
+    /// insert a value into the session, returning the previous value.
+    pub fn insert(&self, key: impl Into<String>, value: impl ToString) -> Option<String> {
+        self.0.changed.store(true, Ordering::Relaxed);
+        self.0.data.lock().insert(key.into(), value.to_string())
+    }
+
+    /// remove a value from the session, returning it.
+    pub fn remove(&self, key: &str) -> Option<String> {
+        let value = self.0.data.lock().remove(key);
+        if value.is_some() {
+            self.0.changed.store(true, Ordering::Relaxed);
+        }
+        value
+    }
+
+    /// remove all values from the session. empty sessions are deleted from the store.
+    pub fn clear(&self) {
+        self.0.changed.store(true, Ordering::Relaxed);
+        self.0.data.lock().clear();
+    }
+
+    /// whether the session contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.0.data.lock().is_empty()
+    }
+}
+
+/// rejection for extracting a [`Session`] without [sessions](crate::Website::sessions) being enabled.
+#[derive(Debug, Clone, Copy)]
+pub struct SessionsDisabled;
+
+impl axum::response::IntoResponse for SessionsDisabled {
+    fn into_response(self) -> Response {
+        warn!("extracting a session requires sessions to be enabled on the website");
+        StatusCode::INTERNAL_SERVER_ERROR.into_response()
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Session {
+    type Rejection = SessionsDisabled;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<Session>()
+            .cloned()
+            .ok_or(SessionsDisabled)
+    }
+}
+
+fn generate_id() -> String {
+    let mut bytes = [0; ID_BYTES];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn is_valid_id(id: &str) -> bool {
+    id.len() == ID_BYTES * 2
+        && id
+            .bytes()
+            .all(|byte| byte.is_ascii_digit() || (b'a'..=b'f').contains(&byte))
+}
+
+fn cookie_id(request: &Request) -> Option<&str> {
+    request
+        .headers()
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|cookies| cookies.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find(|(name, _)| *name == SESSION_COOKIE)
+        .map(|(_, id)| id)
+        .filter(|id| is_valid_id(id))
+}
+
+fn cookie(id: &str, max_age: Option<u64>) -> Option<HeaderValue> {
+    let mut cookie = format!("{}={}; Path=/; SameSite=Lax; HttpOnly", SESSION_COOKIE, id);
+    if let Some(max_age) = max_age {
+        cookie.push_str(&format!("; Max-Age={}", max_age));
+    }
+    HeaderValue::from_str(&cookie).ok()
+}
+
+/// the state of the session middleware.
+#[derive(Clone)]
+pub(crate) struct Sessions {
+    store: Arc<dyn SessionStore>,
+    // the max age of the cookie, it is kept until the browser is closed otherwise
+    lifetime: Option<Duration>,
+}
+
+impl Sessions {
+    pub(crate) fn new(store: Arc<dyn SessionStore>, lifetime: Option<Duration>) -> Self {
+        Self { store, lifetime }
+    }
+}
+
+/// middleware loading the session before and saving it after handling a request.
+pub(crate) async fn load_session(
+    State(Sessions { store, lifetime }): State<Sessions>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let max_age = lifetime.map(|lifetime| lifetime.as_secs());
+    let id = cookie_id(&request).map(str::to_string);
+    let session = match id {
+        Some(id) => match store.load(&id).await {
+            Some(data) => Session::new(Some(id), data),
+            None => Session::new(None, SessionData::new()),
+        },
+        None => Session::new(None, SessionData::new()),
+    };
+    request.extensions_mut().insert(session.clone());
+
+    let mut response = next.run(request).await;
+    if !session.0.changed.load(Ordering::Relaxed) {
+        return response;
+    }
+
+    let data = session.0.data.lock().clone();
+    let set_cookie = match (&session.0.id, data.is_empty()) {
+        (Some(id), true) => {
+            store.remove(id).await;
+            cookie("", Some(0))
+        }
+        (None, true) => None,
+        (Some(id), false) => {
+            store.save(id, &data).await;
+            // renew the cookie, it would expire while the session is still in use otherwise
+            max_age.and_then(|max_age| cookie(id, Some(max_age)))
+        }
+        (None, false) => {
+            let id = generate_id();
+            store.save(&id, &data).await;
+            cookie(&id, max_age)
+        }
+    };
+
+    if let Some(set_cookie) = set_cookie {
+        response
+            .headers_mut()
+            .append(header::SET_COOKIE, set_cookie);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_file_store() {
+        let dir = std::env::temp_dir().join(format!("fishnet-sessions-{}", std::process::id()));
+        let store = FileStore::new(&dir);
+        let id = generate_id();
+        assert!(is_valid_id(&id));
+        assert!(!is_valid_id("../../etc/passwd"));
+
+        assert_eq!(store.load(&id).await, None);
+
+        let mut data = SessionData::new();
+        data.insert("theme".to_string(), "dark".to_string());
+        data.insert("weird; value".to_string(), "a=b&c".to_string());
+        store.save(&id, &data).await;
+        assert_eq!(store.load(&id).await, Some(data.clone()));
+
+        store.remove(&id).await;
+        assert_eq!(store.load(&id).await, None);
+
+        // sessions that weren't used for longer than the idle timeout are gone
+        let store = store.idle_timeout(Duration::from_secs(60 * 60));
+        store.save(&id, &data).await;
+        let unused = SystemTime::now() - Duration::from_secs(2 * 60 * 60);
+        std::fs::File::options()
+            .write(true)
+            .open(store.path(&id))
+            .unwrap()
+            .set_modified(unused)
+            .unwrap();
+        assert_eq!(store.load(&id).await, None);
+        assert!(!store.path(&id).exists());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_memory_store_expiry() {
+        let store = MemoryStore::new().idle_timeout(Duration::from_secs(60 * 60));
+        let id = generate_id();
+        let data = SessionData::from([("theme".to_string(), "dark".to_string())]);
+        store.save(&id, &data).await;
+
+        // every use keeps the session alive for another hour
+        tokio::time::advance(Duration::from_secs(45 * 60)).await;
+        assert_eq!(store.load(&id).await, Some(data.clone()));
+        tokio::time::advance(Duration::from_secs(45 * 60)).await;
+        assert_eq!(store.load(&id).await, Some(data.clone()));
+
+        tokio::time::advance(Duration::from_secs(2 * 60 * 60)).await;
+        assert_eq!(store.load(&id).await, None);
+
+        // unused sessions are cleaned up even if they are never loaded again
+        store.save(&id, &data).await;
+        tokio::time::advance(Duration::from_secs(2 * 60 * 60)).await;
+        store.save(&generate_id(), &data).await;
+        assert_eq!(store.sessions.lock().data.len(), 1);
+    }
+}
@@ -0,0 +1,145 @@
+//! in-memory client for testing a [`Website`] without opening a socket.
+//!
+//! ```rust
+//! use fishnet::{html, testing::TestClient, Page, Website};
+//! use futures::future::FutureExt;
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let page = Page::new("home").with_body(|| async { html! { "hello!" } }.boxed());
+//! let website = Website::new().add_page("/", page).await;
+//!
+//! let client = TestClient::new(website);
+//! let response = client.get("/").await;
+//!
+//! assert_eq!(response.status(), 200);
+//! assert!(response.text().contains("hello!"));
+//! # }
+//! ```
+
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, HeaderMap, Method, Request, StatusCode},
+    Router,
+};
+use tower_service::Service;
+
+use crate::Website;
+
+/// a client sending requests directly to the router of a [`Website`].
+#[derive(Clone)]
+pub struct TestClient {
+    router: Router,
+}
+
+impl TestClient {
+    /// create a new client for the given website.
+    pub fn new(website: Website) -> Self {
+        Self::from_router(website.into_router())
+    }
+
+    /// create a new client for an already built router.
+    pub fn from_router(router: Router) -> Self {
+        Self { router }
+    }
+
+    /// send a `GET` request to the given path.
+    pub async fn get(&self, path: &str) -> TestResponse {
+        self.send(Method::GET, path, None, Body::empty()).await
+    }
+
+    /// send a `POST` request with a url encoded form body to the given path.
+    pub async fn post_form(&self, path: &str, form: &str) -> TestResponse {
+        self.send(
+            Method::POST,
+            path,
+            Some("application/x-www-form-urlencoded"),
+            Body::from(form.to_string()),
+        )
+        .await
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        content_type: Option<&str>,
+        body: Body,
+    ) -> TestResponse {
+        let mut request = Request::builder().method(method).uri(path);
+        if let Some(content_type) = content_type {
+            request = request.header(header::CONTENT_TYPE, content_type);
+        }
+
+        self.request(request.body(body).expect("failed to build request"))
+            .await
+    }
+
+    /// send an arbitrary request.
+    pub async fn request(&self, request: Request<Body>) -> TestResponse {
+        let response = self
+            .router
+            .clone()
+            .call(request)
+            .await
+            .unwrap_or_else(|err| match err {});
+
+        let (parts, body) = response.into_parts();
+        let body = to_bytes(body, usize::MAX)
+            .await
+            .expect("failed to read response body");
+
+        TestResponse {
+            status: parts.status,
+            headers: parts.headers,
+            body: String::from_utf8_lossy(&body).into_owned(),
+        }
+    }
+}
+
+/// a fully received response from a [`TestClient`].
+#[derive(Debug)]
+pub struct TestResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: String,
+}
+
+impl TestResponse {
+    /// the status code of the response.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// all the headers of the response.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// get a single header as a string.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|value| value.to_str().ok())
+    }
+
+    /// the body of the response.
+    pub fn text(&self) -> &str {
+        &self.body
+    }
+
+    /// the value of the first attribute with the given name in the body, e.g. the endpoint of a component in `hx-post`.
+    ///
+    /// the value is returned as it appears in the html, so it is still escaped.
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs(name).next()
+    }
+
+    /// the values of all attributes with the given name in the body, see [`attr`](TestResponse::attr).
+    pub fn attrs<'a>(&'a self, name: &str) -> impl Iterator<Item = &'a str> {
+        let start = format!(" {}=\"", name);
+        let mut rest = self.body.as_str();
+        std::iter::from_fn(move || {
+            rest = &rest[rest.find(&start)? + start.len()..];
+            rest.split('"').next()
+        })
+    }
+}
@@ -1,18 +1,44 @@
 //! Storing and serving multiple [`Page`]s as a website.
 
-use axum::Router;
+use axum::{handler::HandlerWithoutStateExt, middleware, Router};
+use futures::future::{BoxFuture, FutureExt};
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::{TcpListener, ToSocketAddrs};
 use tower_http::compression::CompressionLayer;
 use tower_http::services::ServeDir;
 use tracing::{info, instrument};
 
-use crate::page::{Page, RouterPageExt};
+use crate::page::{BuiltPage, Layout, Page, PageMeta};
+use crate::session::{self, SessionStore};
+
+mod csrf;
+pub use csrf::{CsrfToken, CSRF_COOKIE, CSRF_HEADER};
+
+mod error_pages;
+use error_pages::ErrorPages;
+
+mod export;
+pub use export::{ExportIssue, ExportReport};
 
 /// A simple website builder. A Website consists of multiple [`Page`]s and can additionally serve static files.
 pub struct Website {
     router: Router,
+    pages: Vec<Arc<BuiltPage>>,
+    layouts: Vec<Layout>,
+    error_pages: ErrorPages,
+    default_meta: PageMeta,
 
     serve_dir: Option<String>,
     compression: bool,
+    csrf_protection: bool,
+    session_store: Option<Arc<dyn SessionStore>>,
+    session_lifetime: Option<Duration>,
+
+    shutdown_signal: Option<BoxFuture<'static, ()>>,
 }
 
 impl Website {
@@ -20,40 +46,238 @@ impl Website {
     pub fn new() -> Self {
         Self {
             router: Router::new(),
+            pages: Vec::new(),
+            layouts: Vec::new(),
+            error_pages: ErrorPages::default(),
+            default_meta: PageMeta::new(),
             serve_dir: None,
             compression: false,
+            csrf_protection: false,
+            session_store: None,
+            session_lifetime: None,
+
+            shutdown_signal: None,
         }
     }
 
     /// Add a page to the website.
     ///
     /// This will first initiate a build of the page and afterwards attach the page to the Website at the given path.
+    ///
+    /// The path can contain parameters (e.g. `/blog/:slug`) to serve multiple paths using the same page.
+    /// The values of the parameters are passed to the body set via [`Page::with_param_body`].
     pub async fn add_page(mut self, path: &str, page: Page) -> Self {
-        self.router = self.router.clone().attach_page(path, page).await;
+        let page = page.with_default_meta(&self.default_meta);
+        self.mount_layouts(&page).await;
+        let (built_page, router) = BuiltPage::new(page, path).await;
+        self.router = self.router.merge(router);
+        self.pages.push(built_page);
 
         self
     }
 
+    /// Set the page that is shown when no page, api route or static file matches the request.
+    ///
+    /// The page is served with a `404 Not Found` status.
+    pub async fn not_found_page(mut self, page: Page) -> Self {
+        let page = page.with_default_meta(&self.default_meta);
+        self.mount_layouts(&page).await;
+        let (built_page, router) = BuiltPage::new_error_page(page, "/_fishnet/not-found").await;
+        self.router = self.router.merge(router);
+        self.error_pages.not_found = Some(built_page);
+
+        self
+    }
+
+    /// Set the page that is shown when a request fails with a server error (`5xx`).
+    ///
+    /// Responses that are already html (e.g. a component rendering its own error message) are left untouched.
+    /// The status of the failed request can be read using [`RequestContext::error_status`](crate::page::RequestContext::error_status).
+    pub async fn error_page(mut self, page: Page) -> Self {
+        let page = page.with_default_meta(&self.default_meta);
+        self.mount_layouts(&page).await;
+        let (built_page, router) = BuiltPage::new_error_page(page, "/_fishnet/error").await;
+        self.router = self.router.merge(router);
+        self.error_pages.error = Some(built_page);
+
+        self
+    }
+
+    // the components of a layout are shared between pages, so their api routes are only added once.
+    async fn mount_layouts(&mut self, page: &Page) {
+        let Some(layout) = page.layout() else {
+            return;
+        };
+
+        for layout in layout.chain() {
+            if self.layouts.iter().any(|mounted| mounted.ptr_eq(layout)) {
+                continue;
+            }
+            if let Some(mounted) = self
+                .layouts
+                .iter()
+                .find(|mounted| mounted.api_path() == layout.api_path())
+            {
+                panic!(
+                    "layouts '{}' and '{}' would serve their api routes at the same path, give them different names",
+                    mounted.name(),
+                    layout.name()
+                );
+            }
+
+            self.router = std::mem::take(&mut self.router).merge(layout.make_router().await);
+            self.layouts.push(layout.clone());
+        }
+    }
+
+    /// Set the metadata that is used for everything a page doesn't set itself (see [`Page::with_meta`]).
+    ///
+    /// Pages are built when they are added, so this only applies to pages that are added afterwards.
+    pub fn default_meta(mut self, meta: PageMeta) -> Self {
+        self.default_meta = meta;
+        self
+    }
+
     /// Enable or disable compression for the website.
     pub fn compression(mut self, enable: bool) -> Self {
         self.compression = enable;
         self
     }
 
+    /// Enable or disable protection against cross-site request forgery.
+    ///
+    /// Every visitor gets a random token, which is stored in a cookie and added to the `head` of every page.
+    /// Requests sent by htmx automatically include the token in the [`CSRF_HEADER`].
+    /// All requests except `GET`, `HEAD`, `OPTIONS` and `TRACE` are rejected with `403 Forbidden` if they don't contain the token.
+    ///
+    /// Requests sent without htmx can read the token using [`RequestContext::csrf_token`](crate::page::RequestContext::csrf_token).
+    pub fn csrf_protection(mut self, enable: bool) -> Self {
+        self.csrf_protection = enable;
+        self
+    }
+
+    /// Enable sessions, storing their data in the given store.
+    ///
+    /// Visitors are identified by a cookie, which is only set once something is stored in their session.
+    /// The [`Session`](crate::session::Session) can be extracted in component routes and read from dynamic components
+    /// using [`RequestContext::session`](crate::page::RequestContext::session). See the [`session`](crate::session) module for more.
+    pub fn sessions<S: SessionStore>(mut self, store: S) -> Self {
+        self.session_store = Some(Arc::new(store));
+        self
+    }
+
+    /// Keep the session cookie for the given time instead of until the browser is closed.
+    ///
+    /// The cookie is renewed whenever the session changes. This doesn't affect how long the store keeps the session,
+    /// see [`MemoryStore::idle_timeout`](crate::session::MemoryStore::idle_timeout) for that.
+    pub fn session_lifetime(mut self, lifetime: Duration) -> Self {
+        self.session_lifetime = Some(lifetime);
+        self
+    }
+
     /// Serve a directory as static files.
     pub fn serve_dir(mut self, path: &str) -> Self {
         self.serve_dir = Some(path.to_string());
         self
     }
 
-    /// Start serving the website on the given port.
+    /// Stop serving the website once the given future resolves.
+    ///
+    /// After the future resolves, no new connections are accepted. The website then waits for all in-flight
+    /// requests to finish, stops all component runners and returns from [`serve`](Website::serve).
+    pub fn graceful_shutdown<F>(mut self, signal: F) -> Self
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.shutdown_signal = Some(signal.boxed());
+        self
+    }
+
+    /// Start serving the website on the given port on all interfaces.
     ///
-    /// The returned future will never resolve unless an error occurs.
+    /// The returned future will only resolve if binding to the port fails or a [graceful shutdown](Website::graceful_shutdown) completes.
+    pub async fn serve(self, port: u16) -> io::Result<()> {
+        self.serve_at(SocketAddr::from(([0, 0, 0, 0], port))).await
+    }
+
+    /// Start serving the website on the given address.
+    ///
+    /// This can be used for serving on IPv6 (`"[::]:8080"`) or only locally (`"127.0.0.1:8080"`).
+    /// The returned future will only resolve if binding to the address fails or a [graceful shutdown](Website::graceful_shutdown) completes.
+    pub async fn serve_at<A>(self, addr: A) -> io::Result<()>
+    where
+        A: ToSocketAddrs,
+    {
+        let listener = TcpListener::bind(addr).await?;
+        self.serve_listener(listener).await
+    }
+
+    /// Start serving the website on an already bound listener.
+    ///
+    /// The returned future will only resolve if an error occurs or a [graceful shutdown](Website::graceful_shutdown) completes.
     #[instrument(name = "Website::serve" skip_all, level = "debug")]
-    pub async fn serve(mut self, port: u16) {
+    pub async fn serve_listener(mut self, listener: TcpListener) -> io::Result<()> {
+        let mut pages = self.pages.clone();
+        pages.extend(self.error_pages.not_found.clone());
+        pages.extend(self.error_pages.error.clone());
+        let layouts = self.layouts.clone();
+        let shutdown_signal = self.shutdown_signal.take();
+        let router = self.into_router();
+
+        info!("ready! serving page on {}", listener.local_addr()?);
+        let serve = axum::serve(listener, router);
+        match shutdown_signal {
+            Some(signal) => serve.with_graceful_shutdown(signal).await?,
+            None => serve.await?,
+        }
+
+        info!("shutting down");
+        for page in &pages {
+            page.stop_runners().await;
+        }
+        for layout in &layouts {
+            layout.stop_runners().await;
+        }
+
+        Ok(())
+    }
+
+    /// Turn the website into an axum [`Router`].
+    ///
+    /// The router contains all the pages, the [served directory](Website::serve_dir) and, if enabled, the session, csrf protection and compression layers.
+    /// This is useful for nesting the website into a bigger axum application or for testing it without opening a socket (see [`TestClient`](crate::testing::TestClient)).
+    ///
+    /// Component runners keep running as long as the pages do, since there is no [shutdown](Website::graceful_shutdown) to stop them.
+    pub fn into_router(self) -> Router {
+        let mut router = self.router;
+
         if let Some(path) = self.serve_dir {
-            let serve_dir = ServeDir::new(path).append_index_html_on_directories(true);
-            self.router = self.router.fallback_service(serve_dir);
+            let serve_dir = ServeDir::new(path)
+                .append_index_html_on_directories(true)
+                .fallback(error_pages::not_found.into_service());
+            router = router.fallback_service(serve_dir);
+        } else {
+            router = router.fallback(error_pages::not_found);
+        }
+
+        if !self.error_pages.is_empty() {
+            let error_pages = Arc::new(self.error_pages);
+            router = router.layer(middleware::from_fn_with_state(
+                error_pages,
+                error_pages::render_error_pages,
+            ));
+        }
+
+        if let Some(store) = self.session_store {
+            let sessions = session::Sessions::new(store, self.session_lifetime);
+            router = router.layer(middleware::from_fn_with_state(
+                sessions,
+                session::load_session,
+            ));
+        }
+
+        if self.csrf_protection {
+            router = router.layer(middleware::from_fn(csrf::protect));
         }
 
         if self.compression {
@@ -63,14 +287,9 @@ impl Website {
                 .br(true)
                 .deflate(true);
 
-            self.router = self.router.layer(compression);
+            router = router.layer(compression);
         }
 
-        let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{}", port))
-            .await
-            .unwrap();
-
-        info!("ready! serving page on port {}", port);
-        axum::serve(listener, self.router).await.unwrap();
+        router
     }
 }
@@ -0,0 +1,153 @@
+//! Protecting [`Website`](crate::Website)s against cross-site request forgery.
+//!
+//! Tokens are checked using the double submit cookie pattern: every visitor gets a random token as a cookie, which pages
+//! include in their `head`. Requests sent by htmx automatically copy the token from the page into a header. Requests that
+//! change anything (everything except `GET`, `HEAD`, `OPTIONS` and `TRACE`) are rejected unless the header matches the cookie.
+//! Other sites can't read the cookie, so they can't send a matching header either.
+
+use axum::{
+    extract::Request,
+    http::{header, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use maud::{html, Markup, PreEscaped};
+use rand::RngCore;
+use tracing::debug;
+
+use crate::auth::constant_time_eq;
+
+/// The name of the cookie containing the token.
+pub const CSRF_COOKIE: &str = "fishnet-csrf";
+/// The name of the header requests have to send the token in.
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
+const TOKEN_BYTES: usize = 32;
+
+// copies the token from the meta tag into the headers of every htmx request
+const HTMX_SCRIPT: &str = "document.addEventListener('htmx:configRequest',function(e){\
+const t=document.querySelector('meta[name=\"csrf-token\"]');\
+if(t){e.detail.headers['X-CSRF-Token']=t.content;}});";
+
+/// The CSRF token of the current visitor.
+///
+/// If [CSRF protection](crate::Website::csrf_protection) is enabled, this can be read from the request using
+/// [`RequestContext::csrf_token`](crate::page::RequestContext::csrf_token). This is only needed for sending requests
+/// without htmx, which have to put the token in the [`CSRF_HEADER`] themselves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsrfToken(String);
+
+impl CsrfToken {
+    fn generate() -> Self {
+        let mut bytes = [0; TOKEN_BYTES];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        Self(bytes.iter().map(|byte| format!("{:02x}", byte)).collect())
+    }
+
+    // tokens sent by the client are only accepted if they could have been generated by us
+    fn parse(token: &str) -> Option<Self> {
+        let valid = token.len() == TOKEN_BYTES * 2
+            && token
+                .bytes()
+                .all(|byte| byte.is_ascii_digit() || (b'a'..=b'f').contains(&byte));
+        valid.then(|| Self(token.to_string()))
+    }
+
+    /// The token itself.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The markup added to the `head` of pages, making htmx send the token with every request.
+    pub(crate) fn head(&self) -> Markup {
+        html! {
+            meta name="csrf-token" content=(self.0);
+            script { (PreEscaped(HTMX_SCRIPT)) }
+        }
+    }
+
+    fn matches(&self, other: &str) -> bool {
+        // compare in constant time, so the token can't be guessed byte by byte
+        constant_time_eq(self.0.as_bytes(), other.as_bytes())
+    }
+}
+
+fn is_safe(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE
+    )
+}
+
+fn cookie_token(request: &Request) -> Option<CsrfToken> {
+    request
+        .headers()
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|cookies| cookies.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find(|(name, _)| *name == CSRF_COOKIE)
+        .and_then(|(_, token)| CsrfToken::parse(token))
+}
+
+/// Middleware issuing tokens and rejecting requests without a valid one.
+pub(crate) async fn protect(mut request: Request, next: Next) -> Response {
+    let token = cookie_token(&request);
+
+    if !is_safe(request.method()) {
+        let header = request
+            .headers()
+            .get(CSRF_HEADER)
+            .and_then(|value| value.to_str().ok());
+
+        return match (&token, header) {
+            (Some(token), Some(header)) if token.matches(header) => next.run(request).await,
+            _ => {
+                debug!(
+                    path = request.uri().path(),
+                    "rejecting request without a valid csrf token"
+                );
+                (StatusCode::FORBIDDEN, "invalid csrf token").into_response()
+            }
+        };
+    }
+
+    let (token, is_new) = match token {
+        Some(token) => (token, false),
+        None => (CsrfToken::generate(), true),
+    };
+    request.extensions_mut().insert(token.clone());
+
+    let mut response = next.run(request).await;
+    if is_new {
+        let cookie = format!(
+            "{}={}; Path=/; SameSite=Strict; HttpOnly",
+            CSRF_COOKIE, token.0
+        );
+        if let Ok(cookie) = HeaderValue::from_str(&cookie) {
+            response.headers_mut().append(header::SET_COOKIE, cookie);
+        }
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token() {
+        let token = CsrfToken::generate();
+        assert_eq!(token.as_str().len(), 64);
+        assert_eq!(CsrfToken::parse(token.as_str()), Some(token.clone()));
+        assert_ne!(CsrfToken::generate(), token);
+
+        assert!(token.matches(token.as_str()));
+        assert!(!token.matches(&CsrfToken::generate().0));
+        assert!(!token.matches("short"));
+
+        assert_eq!(CsrfToken::parse("not a token"), None);
+        assert_eq!(CsrfToken::parse(&"G".repeat(64)), None);
+    }
+}
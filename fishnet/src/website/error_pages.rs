@@ -0,0 +1,75 @@
+//! Rendering [`Page`](crate::Page)s as responses to errors.
+
+use axum::{
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Extension,
+};
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+use crate::page::{BuiltPage, PathParams, RequestContext};
+use crate::routes::{self, RouteNotFound};
+
+#[derive(Default, Clone)]
+pub(crate) struct ErrorPages {
+    pub not_found: Option<Arc<BuiltPage>>,
+    pub error: Option<Arc<BuiltPage>>,
+}
+
+impl ErrorPages {
+    pub fn is_empty(&self) -> bool {
+        self.not_found.is_none() && self.error.is_none()
+    }
+}
+
+/// Fallback for requests that don't match any route.
+pub(crate) async fn not_found() -> Response {
+    routes::not_found("")
+}
+
+/// Middleware replacing error responses with the configured error pages.
+pub(crate) async fn render_error_pages(
+    State(pages): State<Arc<ErrorPages>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let (parts, body) = request.into_parts();
+    let response = next.run(Request::from_parts(parts.clone(), body)).await;
+
+    let status = response.status();
+    let page = if status == StatusCode::NOT_FOUND
+        && response.extensions().get::<RouteNotFound>().is_some()
+    {
+        pages.not_found.as_ref()
+    } else if status.is_server_error() && !is_html(&response) {
+        pages.error.as_ref()
+    } else {
+        None
+    };
+
+    let Some(page) = page else {
+        return response;
+    };
+
+    debug!("rendering error page for status {}", status);
+    let request = RequestContext::new(parts, PathParams::default()).with_error_status(status);
+    match BuiltPage::render_request(Extension(page.clone()), request).await {
+        Ok(render) => (status, render).into_response(),
+        Err(error) => {
+            warn!("error page failed to render: {}", error);
+            response
+        }
+    }
+}
+
+// html responses are assumed to already be meant for the visitor (e.g. htmx fragments), so they are left alone.
+fn is_html(response: &Response) -> bool {
+    response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|content_type| content_type.starts_with("text/html"))
+}
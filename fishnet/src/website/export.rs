@@ -0,0 +1,211 @@
+//! Exporting a [`Website`] as a collection of static files.
+
+use axum::{http::StatusCode, Extension};
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::fs;
+use tracing::{debug, instrument, warn};
+
+use super::Website;
+use crate::page::BuiltPage;
+
+/// Something that will not work on the exported website.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExportIssue {
+    /// The component is rendered dynamically. The exported page only contains the output of a single render.
+    DynamicComponent { page: String, component: String },
+    /// The component registered api routes, which can't be served from static files.
+    ApiRoute { page: String, route: String },
+    /// The page has a pattern path (e.g. `/blog/:slug`) and was not exported, since its parameters are unknown.
+    PatternPage { page: String, path: String },
+    /// The page [requires authentication](crate::Page::require_auth) and was not exported.
+    ProtectedPage { page: String },
+    /// The page failed to render and was not exported.
+    RenderFailed { page: String, status: StatusCode },
+}
+
+impl fmt::Display for ExportIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportIssue::DynamicComponent { page, component } => write!(
+                f,
+                "component '{}' on page '{}' is dynamic and will be frozen in its exported state",
+                component, page
+            ),
+            ExportIssue::ApiRoute { page, route } => write!(
+                f,
+                "api route '{}' on page '{}' can't be served statically",
+                route, page
+            ),
+            ExportIssue::PatternPage { page, path } => write!(
+                f,
+                "page '{}' has the pattern path '{}' and was skipped",
+                page, path
+            ),
+            ExportIssue::ProtectedPage { page } => {
+                write!(f, "page '{}' requires authentication and was skipped", page)
+            }
+            ExportIssue::RenderFailed { page, status } => write!(
+                f,
+                "page '{}' failed to render with status {} and was skipped",
+                page, status
+            ),
+        }
+    }
+}
+
+/// The result of exporting a [`Website`].
+#[derive(Debug, Default)]
+pub struct ExportReport {
+    /// All the files that were written.
+    pub files: Vec<PathBuf>,
+    /// Everything that will not work on the exported website.
+    pub issues: Vec<ExportIssue>,
+}
+
+impl ExportReport {
+    /// Whether the exported website behaves exactly like the served one.
+    pub fn is_fully_static(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl Website {
+    /// Export the website as static files into the given directory.
+    ///
+    /// Every page is rendered once and written alongside its script and stylesheet, mirroring the paths they would be served at
+    /// (e.g. a page at `/blog` ends up in `blog/index.html`, `blog/script.js` and `blog/style.css`).
+    /// The contents of the [`serve_dir`](Website::serve_dir) are copied over as well.
+    ///
+    /// Components that can't work without a server (dynamic ones and ones with api routes) are listed in the returned [`ExportReport`].
+    #[instrument(name = "Website::export", skip_all, level = "debug")]
+    pub async fn export(&self, dir: impl AsRef<Path>) -> io::Result<ExportReport> {
+        let dir = dir.as_ref();
+        let mut report = ExportReport::default();
+
+        fs::create_dir_all(dir).await?;
+
+        // pages take priority over the served directory, so they are written afterwards.
+        if let Some(serve_dir) = &self.serve_dir {
+            copy_dir(Path::new(serve_dir), dir, &mut report.files).await?;
+        }
+
+        for page in &self.pages {
+            export_page(page, dir, &mut report).await?;
+        }
+
+        for issue in &report.issues {
+            warn!("{}", issue);
+        }
+
+        Ok(report)
+    }
+}
+
+async fn export_page(
+    page: &Arc<BuiltPage>,
+    dir: &Path,
+    report: &mut ExportReport,
+) -> io::Result<()> {
+    if page.is_pattern() {
+        report.issues.push(ExportIssue::PatternPage {
+            page: page.name().to_string(),
+            path: page.path().to_string(),
+        });
+        return Ok(());
+    }
+    if page.requires_auth() {
+        report.issues.push(ExportIssue::ProtectedPage {
+            page: page.name().to_string(),
+        });
+        return Ok(());
+    }
+
+    debug!("exporting page '{}'", page.name());
+
+    let render = match BuiltPage::render(Extension(page.clone())).await {
+        Ok(render) => render,
+        Err(error) => {
+            report.issues.push(ExportIssue::RenderFailed {
+                page: page.name().to_string(),
+                status: error.status(),
+            });
+            return Ok(());
+        }
+    };
+
+    let html_path = dir.join(relative(page.path())).join("index.html");
+    write_file(&html_path, render.into_string(), &mut report.files).await?;
+
+    let script_path = dir.join(relative(page.script_path()));
+    write_file(&script_path, page.bundled_script().await, &mut report.files).await?;
+
+    let style_path = dir.join(relative(page.style_path()));
+    write_file(&style_path, page.stylesheet().await, &mut report.files).await?;
+
+    // components within the layouts end up in the exported page as well.
+    let layouts = page.layout().into_iter().flat_map(|layout| layout.chain());
+
+    let mut components = page.components();
+    let mut api_routes = page.api_routes().await;
+    for layout in layouts {
+        components.extend(layout.components());
+        api_routes.extend(layout.api_routes().await);
+    }
+
+    for component in components {
+        if component.is_dynamic() {
+            report.issues.push(ExportIssue::DynamicComponent {
+                page: page.name().to_string(),
+                component: component.name().to_string(),
+            });
+        }
+    }
+
+    for route in api_routes {
+        report.issues.push(ExportIssue::ApiRoute {
+            page: page.name().to_string(),
+            route,
+        });
+    }
+
+    Ok(())
+}
+
+// turn an absolute route into a path relative to the export directory.
+fn relative(route: &str) -> &Path {
+    Path::new(route.trim_start_matches('/'))
+}
+
+async fn write_file(path: &Path, contents: String, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+
+    fs::write(path, contents).await?;
+    files.push(path.to_path_buf());
+    Ok(())
+}
+
+async fn copy_dir(from: &Path, to: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    let mut pending = vec![(from.to_path_buf(), to.to_path_buf())];
+
+    while let Some((from, to)) = pending.pop() {
+        fs::create_dir_all(&to).await?;
+
+        let mut entries = fs::read_dir(&from).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let target = to.join(entry.file_name());
+            if entry.file_type().await?.is_dir() {
+                pending.push((entry.path(), target));
+            } else {
+                fs::copy(entry.path(), &target).await?;
+                files.push(target);
+            }
+        }
+    }
+
+    Ok(())
+}
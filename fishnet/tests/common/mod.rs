@@ -0,0 +1,53 @@
+//! helpers shared by the website tests, every test binary only uses some of them.
+#![allow(dead_code)]
+
+use fishnet::testing::TestClient;
+use fishnet::{Page, Website};
+use std::path::PathBuf;
+
+// the element id the component with the given name was rendered with.
+pub fn dom_id<'a>(html: &'a str, name: &str) -> &'a str {
+    let start = html
+        .find(&format!(" id=\"{}_", name))
+        .expect("component should have been rendered")
+        + " id=\"".len();
+    let end = start + html[start..].find('"').unwrap();
+    &html[start..end]
+}
+
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("fishnet-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+// a client for a website serving `page` at `/`.
+pub async fn client(page: Page) -> TestClient {
+    client_with(Website::new(), page).await
+}
+
+// a client for `website`, serving `page` at `/`.
+pub async fn client_with(website: Website, page: Page) -> TestClient {
+    TestClient::new(website.add_page("/", page).await)
+}
+
+// the raw http response of a get request sent to a running server.
+pub async fn raw_get(addr: std::net::SocketAddr, path: &str) -> String {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+    stream
+        .write_all(
+            format!(
+                "GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+                path
+            )
+            .as_bytes(),
+        )
+        .await
+        .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    response
+}
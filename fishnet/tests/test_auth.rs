@@ -0,0 +1,166 @@
+use axum::http::StatusCode;
+use fishnet::component::prelude::*;
+use fishnet::testing::TestClient;
+use fishnet::{Page, Website};
+
+#[cfg(test)]
+use pretty_assertions::assert_eq;
+
+#[tokio::test]
+async fn test_auth() {
+    use fishnet::auth::{Auth, AuthUser, TokenAuth};
+
+    fn admin() -> Auth {
+        Auth::new(TokenAuth::new().token("secret", "admin").cookie("token"))
+            .fallback(html! { "please log in" })
+    }
+
+    #[component(auth = admin())]
+    async fn admin_panel() {
+        let state = state!(());
+
+        #[route("/reset", POST)]
+        async fn reset() -> Markup {
+            html! { "reset" }
+        }
+
+        html! {
+            button hx-post=(format!("{}/reset", state.endpoint())) { "reset everything" }
+        }
+    }
+
+    #[component]
+    async fn guestbook() {
+        let state = state!(());
+
+        #[route("/", GET)]
+        async fn entries() -> Markup {
+            html! { "entries" }
+        }
+
+        #[route("/delete", POST, auth = admin())]
+        async fn delete(AuthUser(user): AuthUser) -> Markup {
+            html! { "deleted by " (user) }
+        }
+
+        html! {
+            div hx-get=(state.endpoint()) {}
+        }
+    }
+
+    #[dyn_component(auth = admin())]
+    async fn admin_stats() {
+        let state = state!(());
+
+        #[route("/", GET)]
+        async fn stats() -> Markup {
+            html! { "stats" }
+        }
+
+        html! {
+            div data-stats=(state.endpoint()) { "admin stats" }
+        }
+    }
+
+    let home = Page::new("home").with_body(|| {
+        async {
+            html! {
+                (c!(admin_panel()))
+                (c!(guestbook()))
+                (c!(admin_stats()))
+            }
+        }
+        .boxed()
+    });
+    let admin_page = Page::new("admin")
+        .with_body(|| {
+            async {
+                let user = request().and_then(|request| request.user().map(str::to_string));
+                html! { "hello " (user.unwrap_or_default()) }
+            }
+            .boxed()
+        })
+        .require_auth(admin().redirect_to("/"));
+
+    let website = Website::new()
+        .add_page("/", home)
+        .await
+        .add_page("/admin", admin_page)
+        .await;
+    let client = TestClient::new(website);
+
+    let request = |method: &str, path: &str, token: Option<&str>| {
+        let mut request = axum::http::Request::builder().method(method).uri(path);
+        if let Some(token) = token {
+            request = request.header("authorization", format!("Bearer {}", token));
+        }
+        request.body(axum::body::Body::empty()).unwrap()
+    };
+
+    let response = client.request(request("GET", "/", None)).await;
+    assert!(response.text().contains("please log in"));
+    assert!(!response.text().contains("reset everything"));
+    let guestbook = response.attr("hx-get").unwrap().to_string();
+
+    let response = client.request(request("GET", "/", Some("secret"))).await;
+    assert!(!response.text().contains("please log in"));
+    let reset = response.attr("hx-post").unwrap().to_string();
+
+    // guarded component routes
+    let response = client.request(request("POST", &reset, None)).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = client.request(request("POST", &reset, Some("wrong"))).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = client
+        .request(request("POST", &reset, Some("secret")))
+        .await;
+    assert_eq!(response.text(), "reset");
+
+    // dynamic components are guarded as well
+    let response = client.request(request("GET", "/", Some("secret"))).await;
+    assert!(response.text().contains("admin stats"));
+    let stats = response.attr("data-stats").unwrap().to_string();
+    let response = client.request(request("GET", &stats, None)).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = client.request(request("GET", &stats, Some("secret"))).await;
+    assert_eq!(response.text(), "stats");
+    let response = client.request(request("GET", "/", None)).await;
+    assert!(!response.text().contains("admin stats"));
+
+    // guarded single routes
+    let response = client.request(request("GET", &guestbook, None)).await;
+    assert_eq!(response.text(), "entries");
+    let delete = format!("{}/delete", guestbook);
+    let response = client.request(request("POST", &delete, None)).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = client
+        .request(request("POST", &delete, Some("secret")))
+        .await;
+    assert_eq!(response.text(), "deleted by admin");
+
+    // guarded pages
+    let response = client.request(request("GET", "/admin", None)).await;
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    assert_eq!(response.header("location"), Some("/"));
+    let response = client
+        .request(
+            axum::http::Request::builder()
+                .uri("/admin")
+                .header("hx-request", "true")
+                .body(axum::body::Body::empty())
+                .unwrap(),
+        )
+        .await;
+    assert_eq!(response.header("hx-redirect"), Some("/"));
+    let response = client
+        .request(
+            axum::http::Request::builder()
+                .uri("/admin")
+                .header("cookie", "token=secret")
+                .body(axum::body::Body::empty())
+                .unwrap(),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.text().contains("hello admin"));
+}
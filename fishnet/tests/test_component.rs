@@ -77,7 +77,10 @@ async fn test_component() {
     let render = result.built_component.render().await;
     assert_eq!(
         render.0,
-        "<div class=\"testing-component\"><div>Hello, world! 0</div></div>"
+        format!(
+            "<div class=\"testing-component\" id=\"{}\"><div>Hello, world! 0</div></div>",
+            result.built_component.dom_id()
+        )
     );
 
     assert!(result.runner.is_none());
@@ -129,7 +132,10 @@ async fn test_component_args() {
 
     assert_eq!(
         render.0,
-        "<div class=\"testing-component\"><div>Hello, world! 42</div></div>"
+        format!(
+            "<div class=\"testing-component\" id=\"{}\"><div>Hello, world! 42</div></div>",
+            result.built_component.dom_id()
+        )
     );
 }
 
@@ -150,7 +156,13 @@ async fn test_component_state_ident() {
     let result = testing_component().build("/").await;
     let render = result.built_component.render().await;
 
-    assert_eq!(render.0, "<div class=\"testing-component\">1</div>");
+    assert_eq!(
+        render.0,
+        format!(
+            "<div class=\"testing-component\" id=\"{}\">1</div>",
+            result.built_component.dom_id()
+        )
+    );
 }
 
 #[tokio::test]
@@ -171,16 +183,31 @@ async fn test_component_staticity() {
     assert!(!result.built_component.is_dynamic());
 
     let render = result.built_component.render().await;
-    assert_eq!(render.0, "<div class=\"testing-component\">1</div>");
+    assert_eq!(
+        render.0,
+        format!(
+            "<div class=\"testing-component\" id=\"{}\">1</div>",
+            result.built_component.dom_id()
+        )
+    );
 
     let render = result.built_component.render().await;
-    assert_eq!(render.0, "<div class=\"testing-component\">1</div>");
+    assert_eq!(
+        render.0,
+        format!(
+            "<div class=\"testing-component\" id=\"{}\">1</div>",
+            result.built_component.dom_id()
+        )
+    );
 
     let render = result.built_component.render_if_static();
     assert!(render.is_some());
     assert_eq!(
         render.unwrap().0,
-        "<div class=\"testing-component\">1</div>"
+        format!(
+            "<div class=\"testing-component\" id=\"{}\">1</div>",
+            result.built_component.dom_id()
+        )
     );
 }
 
@@ -202,10 +229,22 @@ async fn test_component_dynamic() {
     assert!(result.built_component.is_dynamic());
 
     let render = result.built_component.render().await;
-    assert_eq!(render.0, "<div class=\"testing-component\">1</div>");
+    assert_eq!(
+        render.0,
+        format!(
+            "<div class=\"testing-component\" id=\"{}\">1</div>",
+            result.built_component.dom_id()
+        )
+    );
 
     let render = result.built_component.render().await;
-    assert_eq!(render.0, "<div class=\"testing-component\">2</div>");
+    assert_eq!(
+        render.0,
+        format!(
+            "<div class=\"testing-component\" id=\"{}\">2</div>",
+            result.built_component.dom_id()
+        )
+    );
 
     let render = result.built_component.render_if_static();
     assert!(render.is_none());
@@ -0,0 +1,86 @@
+use axum::http::StatusCode;
+use fishnet::component::prelude::*;
+use fishnet::{Page, Website};
+
+mod common;
+use common::client_with;
+
+#[cfg(test)]
+use pretty_assertions::assert_eq;
+
+#[tokio::test]
+async fn test_csrf() {
+    #[component]
+    async fn csrf_counter() {
+        #[route("/", POST)]
+        async fn increment() -> Markup {
+            html! { "incremented" }
+        }
+
+        let state = state!(());
+
+        html! {
+            button hx-post=(state.endpoint()) { "increment" }
+        }
+    }
+
+    let home = Page::new("home").with_body(|| {
+        async {
+            html! {
+                (c!(csrf_counter()))
+            }
+        }
+        .boxed()
+    });
+    let client = client_with(Website::new().csrf_protection(true), home).await;
+
+    let response = client.get("/").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let cookie = response.header("set-cookie").unwrap().to_string();
+    assert!(cookie.starts_with("fishnet-csrf="));
+    let token = cookie["fishnet-csrf=".len()..]
+        .split(';')
+        .next()
+        .unwrap()
+        .to_string();
+    assert!(response
+        .text()
+        .contains(&format!("<meta name=\"csrf-token\" content=\"{}\">", token)));
+    let endpoint = response.attr("hx-post").unwrap().to_string();
+
+    let post = |cookie: Option<&str>, header: Option<&str>| {
+        let mut request = axum::http::Request::builder().method("POST").uri(&endpoint);
+        if let Some(cookie) = cookie {
+            request = request.header("cookie", format!("fishnet-csrf={}", cookie));
+        }
+        if let Some(header) = header {
+            request = request.header("x-csrf-token", header);
+        }
+        request.body(axum::body::Body::empty()).unwrap()
+    };
+
+    // the token is only issued once
+    let response = client
+        .request(
+            axum::http::Request::builder()
+                .uri("/")
+                .header("cookie", format!("fishnet-csrf={}", token))
+                .body(axum::body::Body::empty())
+                .unwrap(),
+        )
+        .await;
+    assert_eq!(response.header("set-cookie"), None);
+    assert!(response.text().contains(&token));
+
+    let response = client.request(post(None, None)).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let response = client.request(post(Some(&token), None)).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let other = "0".repeat(64);
+    let response = client.request(post(Some(&token), Some(&other))).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = client.request(post(Some(&token), Some(&token))).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "incremented");
+}
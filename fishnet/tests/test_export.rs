@@ -0,0 +1,169 @@
+use fishnet::component::prelude::*;
+use fishnet::website::ExportIssue;
+use fishnet::{Page, Website};
+
+mod common;
+use common::{dom_id, temp_dir};
+
+#[cfg(test)]
+use pretty_assertions::assert_eq;
+
+#[tokio::test]
+async fn test_export() {
+    #[component]
+    async fn exported_static() {
+        style!(css! {
+            color: red;
+        });
+
+        html! {
+            "static"
+        }
+    }
+
+    #[dyn_component]
+    async fn exported_dynamic() {
+        html! {
+            "dynamic"
+        }
+    }
+
+    #[component]
+    async fn exported_route() {
+        #[route("/")]
+        async fn root() -> Markup {
+            html! { "route" }
+        }
+
+        html! {
+            "route"
+        }
+    }
+
+    let serve_dir = temp_dir("export-serve");
+    std::fs::create_dir_all(serve_dir.join("img")).unwrap();
+    std::fs::write(serve_dir.join("img/cat.txt"), "meow").unwrap();
+
+    let home = Page::new("home").with_body(|| {
+        async {
+            html! {
+                (c!(exported_static()))
+            }
+        }
+        .boxed()
+    });
+    let blog = Page::new("blog").with_body(|| {
+        async {
+            html! {
+                (c!(exported_dynamic()))
+                (c!(exported_route()))
+            }
+        }
+        .boxed()
+    });
+
+    let website = Website::new()
+        .serve_dir(serve_dir.to_str().unwrap())
+        .add_page("/", home)
+        .await
+        .add_page("/blog", blog)
+        .await;
+
+    let out = temp_dir("export-out");
+    let report = website.export(&out).await.unwrap();
+
+    let index = std::fs::read_to_string(out.join("index.html")).unwrap();
+    assert!(index.contains(&format!(
+        "<div class=\"exported-static\" id=\"{}\">static</div>",
+        dom_id(&index, "ExportedStatic")
+    )));
+    assert!(index.contains("href=\"/style.css\""));
+    assert!(index.contains("src=\"/script.js\""));
+
+    let style = std::fs::read_to_string(out.join("style.css")).unwrap();
+    assert!(style.contains(".exported-static"));
+    assert!(out.join("script.js").exists());
+
+    let blog = std::fs::read_to_string(out.join("blog/index.html")).unwrap();
+    assert!(blog.contains("dynamic"));
+    assert!(out.join("blog/script.js").exists());
+    assert!(out.join("blog/style.css").exists());
+
+    assert_eq!(
+        std::fs::read_to_string(out.join("img/cat.txt")).unwrap(),
+        "meow"
+    );
+
+    assert!(!report.is_fully_static());
+    assert_eq!(report.issues.len(), 2);
+    assert!(report.issues.contains(&ExportIssue::DynamicComponent {
+        page: "blog".to_string(),
+        component: "ExportedDynamic".to_string(),
+    }));
+    assert!(report.issues.iter().any(|issue| matches!(
+        issue,
+        ExportIssue::ApiRoute { page, route } if page == "blog" && route.starts_with("/blog/api/ExportedRoute_")
+    )));
+
+    let _ = std::fs::remove_dir_all(serve_dir);
+    let _ = std::fs::remove_dir_all(out);
+}
+
+#[tokio::test]
+async fn test_export_layout() {
+    #[dyn_component]
+    async fn exported_layout_dynamic() {
+        html! {
+            "layout dynamic"
+        }
+    }
+
+    #[component]
+    async fn exported_layout_route() {
+        #[route("/")]
+        async fn root() -> Markup {
+            html! { "route" }
+        }
+
+        html! {
+            "route"
+        }
+    }
+
+    let layout = fishnet::page::Layout::new("export", |content| {
+        async move {
+            html! {
+                (c!(exported_layout_dynamic()))
+                (c!(exported_layout_route()))
+                (content)
+            }
+        }
+        .boxed()
+    });
+    let home = Page::new("home").with_layout(&layout).with_body(|| {
+        async {
+            html! { "home" }
+        }
+        .boxed()
+    });
+
+    let website = Website::new().add_page("/", home).await;
+    let out = temp_dir("export-layout-out");
+    let report = website.export(&out).await.unwrap();
+
+    let index = std::fs::read_to_string(out.join("index.html")).unwrap();
+    assert!(index.contains("layout dynamic"));
+
+    // components within the layout are reported for the pages using it
+    assert_eq!(report.issues.len(), 2);
+    assert!(report.issues.contains(&ExportIssue::DynamicComponent {
+        page: "home".to_string(),
+        component: "ExportedLayoutDynamic".to_string(),
+    }));
+    assert!(report.issues.iter().any(|issue| matches!(
+        issue,
+        ExportIssue::ApiRoute { page, route } if page == "home" && route.starts_with("/_fishnet/layout/export/api/ExportedLayoutRoute_")
+    )));
+
+    let _ = std::fs::remove_dir_all(out);
+}
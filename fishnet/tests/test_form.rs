@@ -0,0 +1,147 @@
+use fishnet::component::prelude::*;
+use fishnet::form::{Form, FormErrors, FormValues, ValidForm};
+use fishnet::testing::TestClient;
+use fishnet::{Page, Website};
+
+#[cfg(test)]
+use pretty_assertions::assert_eq;
+
+fn not_admin(name: &String) -> Result<(), String> {
+    if name == "admin" {
+        return Err("this name is reserved".to_string());
+    }
+    Ok(())
+}
+
+#[derive(Form, Debug, PartialEq)]
+#[form(submit = "sign up")]
+struct Signup {
+    #[form(label = "user name", min_length = 3, validate = not_admin)]
+    name: String,
+    #[form(input = "password")]
+    password: String,
+    age: Option<u8>,
+    newsletter: bool,
+}
+
+#[test]
+fn test_parse() {
+    let values = FormValues::from_urlencoded(b"name=fish&password=hunter2&newsletter=true");
+    assert_eq!(
+        Signup::parse(&values).unwrap(),
+        Signup {
+            name: "fish".to_string(),
+            password: "hunter2".to_string(),
+            age: None,
+            newsletter: true,
+        }
+    );
+
+    let values = FormValues::from_urlencoded(b"name=admin&age=old");
+    let errors = Signup::parse(&values).unwrap_err();
+    assert_eq!(errors.get("name"), Some("this name is reserved"));
+    assert_eq!(errors.get("password"), Some("this field is required"));
+    assert_eq!(errors.get("age"), Some("must be a valid number"));
+    assert_eq!(errors.get("newsletter"), None);
+}
+
+#[test]
+fn test_render() {
+    let render = Signup::render_form("/api/signup", "Signup_1").into_string();
+
+    assert!(render.starts_with(
+        "<form class=\"fishnet-form\" action=\"/api/signup\" method=\"post\" hx-post=\"/api/signup\" hx-swap=\"outerHTML\">\
+         <input type=\"hidden\" name=\"fishnet-form-id\" value=\"Signup_1\">"
+    ));
+    assert!(render.contains(
+        "<label for=\"Signup_1-signup-name\">user name</label>\
+         <input id=\"Signup_1-signup-name\" name=\"name\" type=\"text\" required minlength=\"3\">"
+    ));
+    assert!(render.contains(
+        "<input id=\"Signup_1-signup-password\" name=\"password\" type=\"password\" required>"
+    ));
+    assert!(render.contains("<input id=\"Signup_1-signup-age\" name=\"age\" type=\"number\">"));
+    assert!(render.contains(
+        "<input id=\"Signup_1-signup-newsletter\" name=\"newsletter\" type=\"checkbox\" value=\"true\">"
+    ));
+    assert!(render.ends_with("<button type=\"submit\">sign up</button></form>"));
+}
+
+#[tokio::test]
+async fn test_submit() {
+    #[component]
+    async fn signup_form() {
+        let state = state!(());
+
+        #[route("/", POST)]
+        async fn submit(ValidForm(signup): ValidForm<Signup>) -> Markup {
+            html! { "welcome, " (signup.name) "!" }
+        }
+
+        html! {
+            (Signup::render_form(state.endpoint(), state.dom_id()))
+        }
+    }
+
+    let page = Page::new("signup").with_body(|| {
+        async {
+            html! {
+                (c!(signup_form()))
+                (c!(signup_form()))
+            }
+        }
+        .boxed()
+    });
+    let client = TestClient::new(Website::new().add_page("/", page).await);
+
+    let response = client.get("/").await;
+    let endpoint = response.attr("hx-post").unwrap().to_string();
+
+    // every instance of the form has its own ids
+    let ids = response
+        .text()
+        .split("name=\"fishnet-form-id\" value=\"")
+        .skip(1)
+        .filter_map(|rest| rest.split('"').next())
+        .collect::<Vec<_>>();
+    assert_eq!(ids.len(), 2);
+    assert_ne!(ids[0], ids[1]);
+    for id in &ids {
+        assert!(response
+            .text()
+            .contains(&format!("<input id=\"{}-signup-name\"", id)));
+    }
+    let id = ids[1].to_string();
+
+    // invalid submissions get the form back, with the submitted values and the errors
+    let response = client
+        .post_form(
+            &endpoint,
+            &format!("fishnet-form-id={}&name=fi&password=secret", id),
+        )
+        .await;
+    assert_eq!(response.status(), 200);
+    let expected_errors = {
+        let mut errors = FormErrors::new();
+        errors.add("name", "must be at least 3 characters long");
+        errors
+    };
+    let mut values = FormValues::default();
+    values.insert("name", "fi");
+    values.insert("password", "secret");
+    assert_eq!(
+        response.text(),
+        fishnet::form::render_form::<Signup>(&endpoint, &id, &values, &expected_errors)
+            .into_string()
+    );
+    assert!(response.text().contains(&format!(
+        "<input id=\"{id}-signup-name\" name=\"name\" type=\"text\" value=\"fi\" required minlength=\"3\" aria-invalid=\"true\" aria-describedby=\"{id}-signup-name-error\">\
+         <p id=\"{id}-signup-name-error\" class=\"fishnet-field-error\">must be at least 3 characters long</p>"
+    )));
+    assert!(!response.text().contains("secret"));
+
+    let response = client
+        .post_form(&endpoint, "name=fish&password=secret")
+        .await;
+    assert_eq!(response.text(), "welcome, fish!");
+}
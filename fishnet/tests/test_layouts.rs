@@ -0,0 +1,120 @@
+use fishnet::component::prelude::*;
+use fishnet::testing::TestClient;
+use fishnet::{Page, Website};
+
+#[cfg(test)]
+use pretty_assertions::assert_eq;
+
+#[tokio::test]
+async fn test_layouts() {
+    use fishnet::page::Layout;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static BUILDS: AtomicUsize = AtomicUsize::new(0);
+
+    #[component]
+    async fn layout_nav() {
+        #[route("/", POST)]
+        async fn visit() -> Markup {
+            html! { "visited" }
+        }
+
+        let state = state_init!({
+            BUILDS.fetch_add(1, Ordering::SeqCst);
+        });
+
+        style!(css! {
+            color: blue;
+        });
+
+        html! {
+            nav hx-post=(state.endpoint()) { "navigation" }
+        }
+    }
+
+    #[component]
+    async fn shared_content() {
+        style!(css! {
+            color: green;
+        });
+
+        html! { "shared" }
+    }
+
+    let site = Layout::new("site", |content| {
+        async move {
+            html! {
+                (c!(layout_nav()))
+                main { (content) }
+            }
+        }
+        .boxed()
+    });
+    let docs = site.nest("docs", |content| {
+        async move {
+            html! {
+                article { (content) }
+            }
+        }
+        .boxed()
+    });
+
+    let home = Page::new("home").with_layout(&site).with_body(|| {
+        async {
+            html! { "home" (c!(shared_content())) }
+        }
+        .boxed()
+    });
+    let guide = Page::new("guide").with_layout(&docs).with_body(|| {
+        async {
+            html! { "guide" (c!(shared_content())) }
+        }
+        .boxed()
+    });
+
+    let website = Website::new()
+        .add_page("/", home)
+        .await
+        .add_page("/guide", guide)
+        .await;
+    let client = TestClient::new(website);
+
+    let home = client.get("/").await;
+    assert!(home.text().contains("</nav></div><main>home"));
+
+    let guide = client.get("/guide").await;
+    assert!(guide.text().contains("</nav></div><main><article>guide"));
+
+    // the navigation is built once and shared by both pages
+    assert_eq!(BUILDS.load(Ordering::SeqCst), 1);
+    let endpoint_home = home.attr("hx-post").unwrap();
+    assert_eq!(endpoint_home, guide.attr("hx-post").unwrap());
+    assert!(endpoint_home.starts_with("/_fishnet/layout/site/api/LayoutNav_"));
+
+    let response = client.post_form(endpoint_home, "").await;
+    assert_eq!(response.text(), "visited");
+
+    // both pages get the styles of the shared components
+    for path in ["/style.css", "/guide/style.css"] {
+        let style = client.get(path).await;
+        assert!(style.text().contains(".layout-nav"));
+        assert!(style.text().contains(".shared-content"));
+    }
+}
+
+#[tokio::test]
+#[should_panic(
+    expected = "layouts 'site' and 'Site' would serve their api routes at the same path"
+)]
+async fn test_layout_name_conflict() {
+    use fishnet::page::Layout;
+
+    let first = Layout::new("site", |content| async move { content }.boxed());
+    let second = Layout::new("Site", |content| async move { content }.boxed());
+
+    Website::new()
+        .add_page("/", Page::new("home").with_layout(&first))
+        .await
+        .add_page("/about", Page::new("about").with_layout(&second))
+        .await;
+}
@@ -0,0 +1,420 @@
+use fishnet::component::prelude::*;
+use fishnet::page::{BuiltPage, Page, RequestContext};
+use futures::StreamExt;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+
+#[cfg(test)]
+use pretty_assertions::assert_eq;
+
+// the dom id of the component with the given name that was built for the page.
+fn dom_id(page: &BuiltPage, name: &str) -> String {
+    page.components()
+        .iter()
+        .find(|component| component.name() == name)
+        .expect("component should have been built")
+        .dom_id()
+        .to_string()
+}
+
+#[tokio::test]
+async fn test_concurrent_render() {
+    #[dyn_component]
+    async fn concurrent_dynamic() {
+        html! {
+            "dynamic"
+        }
+    }
+
+    #[component]
+    async fn concurrent_static() {
+        html! {
+            "static"
+        }
+    }
+
+    let armed = Arc::new(AtomicBool::new(false));
+    let barrier = Arc::new(tokio::sync::Barrier::new(2));
+
+    let page = {
+        let armed = armed.clone();
+        Page::new("concurrent").with_body(move || {
+            let armed = armed.clone();
+            let barrier = barrier.clone();
+            async move {
+                // only continues once both renders are in progress at the same time
+                if armed.load(Ordering::SeqCst) {
+                    barrier.wait().await;
+                }
+
+                html! {
+                    (c!(concurrent_dynamic()))
+                    (c!(concurrent_static()))
+                }
+            }
+            .boxed()
+        })
+    };
+
+    let (built, _) = BuiltPage::new(page, "/").await;
+    armed.store(true, Ordering::SeqCst);
+
+    let (render_one, render_two) = tokio::time::timeout(Duration::from_secs(5), async {
+        tokio::join!(
+            BuiltPage::render(Extension(built.clone())),
+            BuiltPage::render(Extension(built.clone()))
+        )
+    })
+    .await
+    .expect("concurrent renders should not block each other");
+    let (render_one, render_two) = (render_one.unwrap(), render_two.unwrap());
+
+    assert_eq!(render_one.0, render_two.0);
+    assert!(render_one.0.contains(&format!(
+        "<div class=\"concurrent-dynamic\" id=\"{}\">dynamic</div><div class=\"concurrent-static\" id=\"{}\">static</div>",
+        dom_id(&built, "ConcurrentDynamic"),
+        dom_id(&built, "ConcurrentStatic")
+    )));
+}
+
+#[tokio::test]
+async fn test_dom_ids() {
+    #[component]
+    async fn twin() {
+        html! {
+            "twin"
+        }
+    }
+
+    let page = Page::new("twins").with_body(|| {
+        async {
+            html! {
+                (c!(twin()))
+                (c!(twin()))
+            }
+        }
+        .boxed()
+    });
+    let (built, _) = BuiltPage::new(page, "/").await;
+    let render = BuiltPage::render(Extension(built.clone())).await.unwrap();
+
+    // every c! rendering the component gets its own element id
+    let mut ids: Vec<_> = built
+        .components()
+        .iter()
+        .map(|component| component.dom_id().to_string())
+        .collect();
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), 2);
+    for id in ids {
+        assert_eq!(
+            render.0.matches(&format!("id=\"{}\"", id)).count(),
+            1,
+            "{} should be used exactly once",
+            id
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_failing_render() {
+    static FAIL: AtomicBool = AtomicBool::new(true);
+    static RENDERS: AtomicUsize = AtomicUsize::new(0);
+
+    #[component]
+    async fn failing_static() -> Result<Markup, RenderError> {
+        if FAIL.load(Ordering::SeqCst) {
+            return Err(RenderError::new(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "try again later",
+            ));
+        }
+
+        Ok(html! {
+            "recovered"
+        })
+    }
+
+    #[component]
+    async fn failing_top() -> Result<Markup, RenderError> {
+        RENDERS.fetch_add(1, Ordering::SeqCst);
+        if FAIL.load(Ordering::SeqCst) {
+            return Err(RenderError::new(StatusCode::SERVICE_UNAVAILABLE, "not yet"));
+        }
+        Ok(html! { "top" })
+    }
+
+    #[component]
+    async fn failing_parent() {
+        html! {
+            (c!(failing_static()))
+        }
+    }
+
+    let page = Page::new("failing").with_body(|| {
+        async {
+            html! {
+                (c!(failing_parent()))
+                (c!(failing_top()))
+            }
+        }
+        .boxed()
+    });
+
+    let (built, _) = BuiltPage::new(page, "/").await;
+
+    let renders = RENDERS.load(Ordering::SeqCst);
+    let error = BuiltPage::render(Extension(built.clone()))
+        .await
+        .unwrap_err();
+    assert_eq!(error.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert!(built.components().is_empty());
+    // the failed pre-render isn't repeated within the same page render
+    assert_eq!(RENDERS.load(Ordering::SeqCst), renders + 1);
+
+    // failed components are not cached, so they get another chance
+    FAIL.store(false, Ordering::SeqCst);
+    let render = BuiltPage::render(Extension(built.clone())).await.unwrap();
+    assert!(render.0.contains(&format!(
+        "<div class=\"failing-static\" id=\"{}\">recovered</div>",
+        dom_id(&built, "FailingStatic")
+    )));
+    assert_eq!(built.components().len(), 3);
+
+    // ...and stay static once they succeeded
+    FAIL.store(true, Ordering::SeqCst);
+    assert!(BuiltPage::render(Extension(built.clone())).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_static_becoming_dynamic() {
+    use std::sync::OnceLock;
+
+    static GROWN: AtomicBool = AtomicBool::new(false);
+    static RENDERS: AtomicUsize = AtomicUsize::new(0);
+    static STATE: OnceLock<ComponentState<()>> = OnceLock::new();
+
+    #[dyn_component]
+    async fn growing_child() {
+        html! {
+            "grown"
+        }
+    }
+
+    #[component]
+    async fn growing() {
+        let state = state!(());
+        let _ = STATE.set(state.clone());
+        RENDERS.fetch_add(1, Ordering::SeqCst);
+
+        html! {
+            @if GROWN.load(Ordering::SeqCst) {
+                (c!(growing_child()))
+            } @else {
+                "small"
+            }
+        }
+    }
+
+    #[component]
+    async fn growing_parent() {
+        html! {
+            (c!(growing()))
+        }
+    }
+
+    let page = Page::new("growing").with_body(|| {
+        async {
+            html! {
+                (c!(growing_parent()))
+            }
+        }
+        .boxed()
+    });
+    let (built, _) = BuiltPage::new(page, "/").await;
+    let is_dynamic = |name: &str| {
+        built
+            .components()
+            .iter()
+            .find(|component| component.name() == name)
+            .unwrap()
+            .is_dynamic()
+    };
+    assert!(!is_dynamic("Growing"));
+    assert!(!is_dynamic("GrowingParent"));
+
+    GROWN.store(true, Ordering::SeqCst);
+    STATE.get().unwrap().invalidate();
+    let render = BuiltPage::render(Extension(built.clone())).await.unwrap();
+    assert!(render.0.contains(&format!(
+        "<div class=\"growing\" id=\"{}\"><div class=\"growing-child\" id=\"{}\">grown</div></div>",
+        dom_id(&built, "Growing"),
+        dom_id(&built, "GrowingChild")
+    )));
+
+    // both the component and its static parent are dynamic from now on...
+    assert!(is_dynamic("Growing"));
+    assert!(is_dynamic("GrowingParent"));
+
+    // ...so they are rendered exactly once per page render
+    for _ in 0..2 {
+        let renders = RENDERS.load(Ordering::SeqCst);
+        let render = BuiltPage::render(Extension(built.clone())).await.unwrap();
+        assert!(render.0.contains("grown"));
+        assert_eq!(RENDERS.load(Ordering::SeqCst), renders + 1);
+    }
+}
+
+#[tokio::test]
+async fn test_failing_body() {
+    #[dyn_component]
+    async fn failing_dynamic() -> Result<Markup, StatusCode> {
+        let value: usize = "not a number"
+            .parse()
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        Ok(html! {
+            (value)
+        })
+    }
+
+    let page = Page::new("failing body")
+        .try_with_body(|| async { Err::<Markup, _>((StatusCode::FORBIDDEN, "no access")) }.boxed());
+    let (built, _) = BuiltPage::new(page, "/").await;
+    let error = BuiltPage::render(Extension(built)).await.unwrap_err();
+    assert_eq!(error.status(), StatusCode::FORBIDDEN);
+
+    let page = Page::new("failing dynamic").with_body(|| {
+        async {
+            html! {
+                (c!(failing_dynamic()))
+            }
+        }
+        .boxed()
+    });
+    let (built, _) = BuiltPage::new(page, "/").await;
+    let error = BuiltPage::render(Extension(built)).await.unwrap_err();
+    assert_eq!(error.status(), StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+#[tokio::test]
+async fn test_streaming() {
+    static RELEASE: AtomicBool = AtomicBool::new(true);
+
+    #[dyn_component]
+    async fn slow_dynamic() {
+        while !RELEASE.load(Ordering::SeqCst) {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+
+        html! {
+            "finally done"
+        }
+    }
+
+    #[component]
+    async fn quick_static() {
+        html! {
+            "right away"
+        }
+    }
+
+    let page = Page::new("streaming").streaming(true).with_body(|| {
+        async {
+            html! {
+                (c!(slow_dynamic()))
+                (c!(quick_static()))
+            }
+        }
+        .boxed()
+    });
+
+    // the pre-render builds both components
+    let (built, _) = BuiltPage::new(page, "/").await;
+    RELEASE.store(false, Ordering::SeqCst);
+
+    let (parts, _) = axum::http::Request::new(()).into_parts();
+    let request = RequestContext::new(parts, Default::default());
+    let body = tokio::time::timeout(
+        Duration::from_secs(5),
+        BuiltPage::render_stream(Extension(built.clone()), request),
+    )
+    .await
+    .expect("the page should be sent before the slow component finishes")
+    .unwrap();
+
+    let mut chunks = body.into_data_stream();
+    let first = chunks.next().await.unwrap().unwrap();
+    let first = String::from_utf8_lossy(&first).into_owned();
+    assert!(first.starts_with("<!DOCTYPE html><html lang=\"en\"><head>"));
+    assert!(first.contains("<template id=\"fishnet-stream-0\"></template>"));
+    assert!(first.contains(&format!(
+        "<div class=\"quick-static\" id=\"{}\">right away</div>",
+        dom_id(&built, "QuickStatic")
+    )));
+    assert!(!first.contains("finally done"));
+
+    RELEASE.store(true, Ordering::SeqCst);
+    let mut rest = String::new();
+    while let Some(chunk) = chunks.next().await {
+        rest.push_str(&String::from_utf8_lossy(&chunk.unwrap()));
+    }
+    assert!(rest.starts_with(&format!(
+        "<template data-fishnet-stream=\"0\"><div class=\"slow-dynamic\" id=\"{}\">finally done</div></template><script>fishnetSwap(0)</script>",
+        dom_id(&built, "SlowDynamic")
+    )));
+    assert!(rest.ends_with("</script></html>"));
+}
+
+#[tokio::test]
+async fn test_streaming_isolated_renders() {
+    use std::sync::atomic::AtomicU64;
+
+    static KEY: AtomicU64 = AtomicU64::new(0);
+
+    #[component]
+    async fn slow_static_child() {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        html! { "child" }
+    }
+
+    // builds a new static child on every render, which is pre-rendered across an await
+    #[dyn_component]
+    async fn building_dynamic() {
+        let key = KEY.fetch_add(1, Ordering::SeqCst);
+        html! { (c!(key = key, slow_static_child())) }
+    }
+
+    #[dyn_component]
+    async fn request_dynamic() {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        let has_request = request().is_some();
+        html! { "has request: " (has_request) }
+    }
+
+    let page = Page::new("isolated").streaming(true).with_body(|| {
+        async {
+            html! {
+                (c!(building_dynamic()))
+                (c!(request_dynamic()))
+            }
+        }
+        .boxed()
+    });
+    let (built, _) = BuiltPage::new(page, "/").await;
+
+    let (parts, _) = axum::http::Request::new(()).into_parts();
+    let request = RequestContext::new(parts, Default::default());
+    let body = BuiltPage::render_stream(Extension(built), request)
+        .await
+        .unwrap();
+
+    let mut chunks = body.into_data_stream();
+    let mut rendered = String::new();
+    while let Some(chunk) = chunks.next().await {
+        rendered.push_str(&String::from_utf8_lossy(&chunk.unwrap()));
+    }
+    assert!(rendered.contains("has request: true"));
+    assert!(rendered.contains("child"));
+}
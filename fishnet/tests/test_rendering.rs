@@ -0,0 +1,500 @@
+use axum::{http::StatusCode, response::IntoResponse};
+use fishnet::component::prelude::*;
+use fishnet::testing::TestClient;
+use fishnet::{Page, Website};
+
+mod common;
+use common::{client, dom_id};
+
+#[cfg(test)]
+use pretty_assertions::assert_eq;
+
+#[tokio::test]
+async fn test_keyed_components() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct Counter {
+        id: usize,
+        clicks: AtomicUsize,
+    }
+
+    #[component]
+    async fn counter(id: usize) {
+        let state = state_init!(Arc::new(Counter {
+            id,
+            clicks: AtomicUsize::new(0),
+        }));
+
+        #[route("/", POST)]
+        async fn click(state: Extension<ComponentState<Arc<Counter>>>) -> Markup {
+            let clicks = state.clicks.fetch_add(1, Ordering::SeqCst) + 1;
+            html! { (state.id) ":" (clicks) }
+        }
+
+        html! {
+            button hx-post=(state.endpoint()) { (state.id) }
+        }
+    }
+
+    let page = Page::new("counters").with_body(|| {
+        async {
+            html! {
+                @for id in [1, 2, 3] {
+                    (c!(key = id, counter(id)))
+                }
+            }
+        }
+        .boxed()
+    });
+    let client = client(page).await;
+
+    let response = client.get("/").await;
+    let endpoints: Vec<_> = response.attrs("hx-post").map(str::to_string).collect();
+    assert_eq!(endpoints.len(), 3);
+    assert_ne!(endpoints[0], endpoints[1]);
+    assert_ne!(endpoints[1], endpoints[2]);
+    assert!(response.text().contains(">1</button>"));
+    assert!(response.text().contains(">2</button>"));
+    assert!(response.text().contains(">3</button>"));
+
+    // every instance keeps its own state
+    assert_eq!(client.post_form(&endpoints[1], "").await.text(), "2:1");
+    assert_eq!(client.post_form(&endpoints[1], "").await.text(), "2:2");
+    assert_eq!(client.post_form(&endpoints[0], "").await.text(), "1:1");
+
+    // rendering again reuses the existing instances
+    let response = client.get("/").await;
+    assert!(response.text().contains(&endpoints[2]));
+}
+
+#[tokio::test]
+async fn test_invalidate() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static PARENT_RENDERS: AtomicUsize = AtomicUsize::new(0);
+
+    #[component]
+    async fn latest_post() {
+        let state = state_init!(Arc::new(parking_lot::Mutex::new("first post".to_string())));
+
+        #[route("/", POST)]
+        async fn publish(
+            state: Extension<ComponentState<Arc<parking_lot::Mutex<String>>>>,
+        ) -> Markup {
+            *state.lock() = "second post".to_string();
+            state.invalidate();
+            html! {}
+        }
+
+        let post = state.lock().clone();
+        html! {
+            span hx-post=(state.endpoint()) { (post) }
+        }
+    }
+
+    #[component]
+    async fn sidebar() {
+        PARENT_RENDERS.fetch_add(1, Ordering::SeqCst);
+
+        html! {
+            aside { (c!(latest_post())) }
+        }
+    }
+
+    let page = Page::new("blog").with_body(|| async { c!(sidebar()) }.boxed());
+    let client = client(page).await;
+
+    let response = client.get("/").await;
+    assert!(response.text().contains("first post"));
+    let endpoint = response.attr("hx-post").unwrap().to_string();
+
+    // static components are cached...
+    client.get("/").await;
+    let renders = PARENT_RENDERS.load(Ordering::SeqCst);
+
+    // ...until they are invalidated, which also invalidates their static parents
+    client.post_form(&endpoint, "").await;
+    let response = client.get("/").await;
+    assert!(response.text().contains(&format!(
+        "<aside><div class=\"latest-post\" id=\"{}\"><span hx-post=\"{}\">second post</span></div></aside>",
+        dom_id(response.text(), "LatestPost"),
+        endpoint
+    )));
+    assert_eq!(PARENT_RENDERS.load(Ordering::SeqCst), renders + 1);
+
+    // ...and cached again afterwards
+    client.get("/").await;
+    assert_eq!(PARENT_RENDERS.load(Ordering::SeqCst), renders + 1);
+}
+
+#[tokio::test]
+async fn test_cached_components() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    static RENDERS: AtomicUsize = AtomicUsize::new(0);
+    static PARENT_RENDERS: AtomicUsize = AtomicUsize::new(0);
+
+    #[component(cache = "5m")]
+    async fn render_count() {
+        let renders = RENDERS.fetch_add(1, Ordering::SeqCst) + 1;
+
+        html! {
+            "render " (renders)
+        }
+    }
+
+    #[component]
+    async fn cached_parent() {
+        PARENT_RENDERS.fetch_add(1, Ordering::SeqCst);
+
+        html! {
+            (c!(render_count()))
+        }
+    }
+
+    let page = Page::new("cached").with_body(|| async { c!(cached_parent()) }.boxed());
+    let client = client(page).await;
+
+    let first = client.get("/").await;
+    assert!(first.text().contains("render 1"));
+    let parent_renders = PARENT_RENDERS.load(Ordering::SeqCst);
+
+    // the render is cached until it expires...
+    assert_eq!(client.get("/").await.text(), first.text());
+    assert_eq!(PARENT_RENDERS.load(Ordering::SeqCst), parent_renders);
+
+    // ...and then rendered again once, together with its static parent
+    tokio::time::pause();
+    tokio::time::advance(Duration::from_secs(6 * 60)).await;
+    let second = client.get("/").await;
+    assert!(second.text().contains("render 2"));
+    assert_eq!(PARENT_RENDERS.load(Ordering::SeqCst), parent_renders + 1);
+    assert_eq!(client.get("/").await.text(), second.text());
+}
+
+#[tokio::test]
+async fn test_slots() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static CARD_RENDERS: AtomicUsize = AtomicUsize::new(0);
+
+    #[component]
+    async fn slot_card() {
+        CARD_RENDERS.fetch_add(1, Ordering::SeqCst);
+
+        html! {
+            h2 { (slot("title")) }
+            div.content { (children()) }
+        }
+    }
+
+    #[component]
+    async fn slot_static() {
+        html! { "static child" }
+    }
+
+    #[dyn_component]
+    async fn slot_dynamic() {
+        let name = request().and_then(|request| request.query("name"));
+        html! { "hello " (name.unwrap_or_default()) }
+    }
+
+    #[component]
+    async fn slow_card() {
+        // the render with the older slots finishes last
+        let slow = children().into_string().contains("fish");
+        tokio::time::sleep(std::time::Duration::from_millis(if slow { 40 } else { 10 })).await;
+        html! {
+            div.content { (children()) }
+        }
+    }
+
+    let home = Page::new("home").with_body(|| {
+        async {
+            html! {
+                (c!(slot_card(), title = html! { "static" }, children = html! { (c!(slot_static())) }))
+                (c!(slot_card(), html! { (c!(slot_dynamic())) }))
+            }
+        }
+        .boxed()
+    });
+    let slow = Page::new("slow").with_body(|| {
+        async {
+            html! {
+                (c!(slow_card(), html! { "hello " (request().and_then(|request| request.query("name")).unwrap_or_default()) }))
+            }
+        }
+        .boxed()
+    });
+    let website = Website::new()
+        .add_page("/", home)
+        .await
+        .add_page("/slow", slow)
+        .await;
+    let client = TestClient::new(website);
+
+    let response = client.get("/?name=fish").await;
+    assert!(response
+        .text()
+        .contains("<h2>static</h2><div class=\"content\">"));
+    assert!(response.text().contains("static child"));
+    assert!(response.text().contains("<h2></h2>"));
+    assert!(response.text().contains("hello fish"));
+    let renders = CARD_RENDERS.load(Ordering::SeqCst);
+
+    // the card with static slots stays cached, the one with dynamic slots is rendered again when they change
+    let response = client.get("/?name=net").await;
+    assert!(response.text().contains("hello net"));
+    assert_eq!(CARD_RENDERS.load(Ordering::SeqCst), renders + 1);
+
+    let response = client.get("/?name=net").await;
+    assert!(response.text().contains("hello net"));
+    assert_eq!(CARD_RENDERS.load(Ordering::SeqCst), renders + 1);
+
+    // concurrent renders with different slots each get their own content
+    client.get("/slow?name=first").await;
+    let (fish, net) = futures::join!(client.get("/slow?name=fish"), client.get("/slow?name=net"));
+    assert!(fish.text().contains("hello fish"));
+    assert!(!fish.text().contains("hello net"));
+    assert!(net.text().contains("hello net"));
+    assert!(!net.text().contains("hello fish"));
+
+    let net = client.get("/slow?name=net").await;
+    assert!(net.text().contains("hello net"));
+}
+
+#[tokio::test]
+async fn test_wrapper() {
+    #[component(tag = "nav", class = "sidebar", attrs = [("aria-label", "main")])]
+    async fn wrapped_nav() {
+        html! { a href="/" { "home" } }
+    }
+
+    #[component(fragment)]
+    async fn wrapped_entry() {
+        html! { li { "entry" } }
+    }
+
+    let home = Page::new("home").with_body(|| {
+        async {
+            html! {
+                (c!(wrapped_nav()))
+                ul { (c!(wrapped_entry())) }
+            }
+        }
+        .boxed()
+    });
+    let client = client(home).await;
+
+    let response = client.get("/").await;
+    assert!(response.text().contains(&format!(
+        "<nav class=\"wrapped-nav sidebar\" id=\"{}\" aria-label=\"main\"><a href=\"/\">home</a></nav>",
+        dom_id(response.text(), "WrappedNav")
+    )));
+    assert!(response.text().contains("<ul><li>entry</li></ul>"));
+}
+
+#[tokio::test]
+async fn test_dom_id() {
+    #[component]
+    async fn swapping_counter() {
+        let state = state!(());
+
+        #[route("/", POST)]
+        async fn increment(state: Extension<ComponentState<()>>) -> impl IntoResponse {
+            (state.swap_self(), html! { "incremented" })
+        }
+
+        html! {
+            button hx-post=(state.endpoint()) hx-target=(state.target()) { "increment" }
+        }
+    }
+
+    #[component(attrs = [("id", "custom")])]
+    async fn custom_id() {
+        let state = state!(());
+        html! { (state.target()) }
+    }
+
+    let home = Page::new("home").with_body(|| {
+        async {
+            html! {
+                (c!(swapping_counter()))
+                (c!(custom_id()))
+            }
+        }
+        .boxed()
+    });
+    let client = client(home).await;
+
+    let response = client.get("/").await;
+    let endpoint = response.attr("hx-post").unwrap().to_string();
+    let target = response.attr("hx-target").unwrap().to_string();
+    let dom_id = response.attr("id").unwrap();
+    assert_eq!(target, format!("#{}", dom_id));
+    assert!(response
+        .text()
+        .contains("<div class=\"custom-id\" id=\"custom\">#custom</div>"));
+
+    let response = client.post_form(&endpoint, "").await;
+    assert_eq!(response.header("hx-retarget"), Some(target.as_str()));
+    assert_eq!(response.header("hx-reswap"), Some("innerHTML"));
+    assert_eq!(response.text(), "incremented");
+}
+
+#[tokio::test]
+async fn test_render_route() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static RENDERS: AtomicUsize = AtomicUsize::new(0);
+    static CHILD_RENDERS: AtomicUsize = AtomicUsize::new(0);
+
+    #[component]
+    async fn refreshed_child() {
+        CHILD_RENDERS.fetch_add(1, Ordering::SeqCst);
+        html! { "child" }
+    }
+
+    #[component(render_route)]
+    async fn refreshed() {
+        let state = state!(());
+        let renders = RENDERS.fetch_add(1, Ordering::SeqCst) + 1;
+
+        html! {
+            span hx-get=(state.render_url()) { "render " (renders) }
+            (c!(refreshed_child()))
+        }
+    }
+
+    #[component(render_route)]
+    async fn refreshed_post() {
+        let state = state!(());
+        let slug = request()
+            .and_then(|request| request.params().get("slug").map(str::to_string))
+            .unwrap_or_default();
+
+        html! {
+            span hx-get=(state.render_url()) { "post " (slug) }
+            (children())
+        }
+    }
+
+    let home = Page::new("home").with_body(|| {
+        async {
+            html! { (c!(refreshed())) }
+        }
+        .boxed()
+    });
+    let post = Page::new("post").with_body(|| {
+        async {
+            html! { (c!(refreshed_post(), html! { p { "slotted" } })) }
+        }
+        .boxed()
+    });
+    let website = Website::new()
+        .add_page("/", home)
+        .await
+        .add_page("/posts/:slug", post)
+        .await;
+    let client = TestClient::new(website);
+
+    let response = client.get("/").await;
+    assert!(response.text().contains("render 1"));
+    let render_url = response.attr("hx-get").unwrap().to_string();
+    assert!(render_url.ends_with("/render"));
+
+    // only the content is rendered, children are reused
+    let response = client.get(&render_url).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.text().starts_with("<span"));
+    assert!(response.text().contains("render 2"));
+    assert!(response.text().contains("child"));
+    assert_eq!(CHILD_RENDERS.load(Ordering::SeqCst), 1);
+
+    // the render gets the parameters of the page it was requested from and the slots of the latest render
+    let render_from = |url: String, page: &str| {
+        axum::http::Request::builder()
+            .uri(url)
+            .header("hx-current-url", format!("http://localhost{}", page))
+            .body(axum::body::Body::empty())
+            .unwrap()
+    };
+
+    let response = client.get("/posts/hello").await;
+    let render_url = response.attr("hx-get").unwrap().to_string();
+    assert!(render_url.starts_with("/posts/_/api/RefreshedPost_"));
+
+    let response = client
+        .request(render_from(render_url.clone(), "/posts/hello"))
+        .await;
+    assert_eq!(
+        response.text(),
+        format!(
+            "<span hx-get=\"{}\">post hello</span><p>slotted</p>",
+            render_url
+        )
+    );
+
+    // the component is shared with other slugs, which still render their own parameters
+    let response = client.get("/posts/world").await;
+    assert_eq!(response.attr("hx-get").unwrap(), render_url);
+
+    let response = client
+        .request(render_from(render_url.clone(), "/posts/world"))
+        .await;
+    assert_eq!(
+        response.text(),
+        format!(
+            "<span hx-get=\"{}\">post world</span><p>slotted</p>",
+            render_url
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_lazy() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static RENDERS: AtomicUsize = AtomicUsize::new(0);
+
+    #[component(lazy, placeholder = html! { "loading..." })]
+    async fn lazy_report() {
+        RENDERS.fetch_add(1, Ordering::SeqCst);
+        html! { "report done" }
+    }
+
+    #[component(lazy = "revealed")]
+    async fn lazy_footer() {
+        html! { "footer" }
+    }
+
+    let home = Page::new("home").with_body(|| {
+        async {
+            html! {
+                (c!(lazy_report()))
+                (c!(lazy_footer()))
+            }
+        }
+        .boxed()
+    });
+    let client = client(home).await;
+
+    // the page only contains the placeholders
+    let response = client.get("/").await;
+    assert!(response.text().contains("hx-trigger=\"load\""));
+    assert!(response.text().contains("hx-trigger=\"revealed\""));
+    assert!(response.text().contains("loading..."));
+    assert!(!response.text().contains("report done"));
+    assert_eq!(RENDERS.load(Ordering::SeqCst), 0);
+
+    let lazy_url = response.attr("hx-get").unwrap().to_string();
+    assert!(lazy_url.starts_with("/api/"));
+
+    // the content is cached once it was loaded
+    let response = client.get(&lazy_url).await;
+    assert_eq!(response.text(), "report done");
+    let response = client.get(&lazy_url).await;
+    assert_eq!(response.text(), "report done");
+    assert_eq!(RENDERS.load(Ordering::SeqCst), 1);
+}
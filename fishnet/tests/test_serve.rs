@@ -0,0 +1,161 @@
+use axum::{http::StatusCode, Form};
+use fishnet::component::prelude::*;
+use fishnet::testing::TestClient;
+use fishnet::{Page, Website};
+
+mod common;
+use common::{dom_id, raw_get, temp_dir};
+
+#[cfg(test)]
+use pretty_assertions::assert_eq;
+
+#[tokio::test]
+async fn test_serve_listener_graceful_shutdown() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    static RUNNER_STOPPED: AtomicBool = AtomicBool::new(false);
+
+    #[component]
+    async fn serve_runner() {
+        let _state = state!(());
+
+        runner!({
+            struct StopGuard;
+            impl Drop for StopGuard {
+                fn drop(&mut self) {
+                    RUNNER_STOPPED.store(true, Ordering::SeqCst);
+                }
+            }
+
+            let _guard = StopGuard;
+            std::future::pending::<()>().await;
+        });
+
+        html! {
+            "serving"
+        }
+    }
+
+    let page = Page::new("serve").with_body(|| {
+        async {
+            html! {
+                (c!(serve_runner()))
+            }
+        }
+        .boxed()
+    });
+
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+    let website = Website::new()
+        .add_page("/", page)
+        .await
+        .graceful_shutdown(async {
+            let _ = shutdown_rx.await;
+        });
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = tokio::spawn(website.serve_listener(listener));
+
+    let response = raw_get(addr, "/").await;
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(response.contains(&format!(
+        "<div class=\"serve-runner\" id=\"{}\">serving</div>",
+        dom_id(&response, "ServeRunner")
+    )));
+    assert!(!RUNNER_STOPPED.load(Ordering::SeqCst));
+
+    shutdown_tx.send(()).unwrap();
+    server.await.unwrap().unwrap();
+
+    assert!(RUNNER_STOPPED.load(Ordering::SeqCst));
+    assert!(tokio::net::TcpStream::connect(addr).await.is_err());
+}
+
+#[tokio::test]
+async fn test_serve_bind_error() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let result = Website::new().serve_at(addr).await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_client() {
+    #[component]
+    async fn client_button() {
+        #[route("/", POST)]
+        async fn click(Form(form): Form<std::collections::HashMap<String, String>>) -> Markup {
+            html! { "clicked " (form["name"]) }
+        }
+
+        let state = state!(());
+
+        style!(css! {
+            color: red;
+        });
+
+        html! {
+            button hx-post=(state.endpoint()) { "click me" }
+        }
+    }
+
+    let serve_dir = temp_dir("client-serve");
+    std::fs::create_dir_all(&serve_dir).unwrap();
+    std::fs::write(serve_dir.join("robots.txt"), "hello robots").unwrap();
+
+    let home = Page::new("home").with_body(|| {
+        async {
+            html! { "home" }
+        }
+        .boxed()
+    });
+    let blog = Page::new("blog").with_body(|| {
+        async {
+            html! {
+                (c!(client_button()))
+            }
+        }
+        .boxed()
+    });
+
+    let website = Website::new()
+        .serve_dir(serve_dir.to_str().unwrap())
+        .add_page("/", home)
+        .await
+        .add_page("/blog", blog)
+        .await;
+    let client = TestClient::new(website);
+
+    let response = client.get("/").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.text().contains("home"));
+
+    let response = client.get("/blog").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let endpoint = response.attr("hx-post").unwrap().to_string();
+    assert!(endpoint.starts_with("/blog/api/ClientButton_"));
+
+    let response = client.get("/blog/style.css").await;
+    assert_eq!(response.header("content-type"), Some("text/css"));
+    assert!(response.text().contains(".client-button"));
+
+    let response = client.get("/blog/script.js").await;
+    assert_eq!(
+        response.header("content-type"),
+        Some("application/javascript")
+    );
+
+    let response = client.post_form(&endpoint, "name=fish").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "clicked fish");
+
+    let response = client.get("/robots.txt").await;
+    assert_eq!(response.text(), "hello robots");
+
+    let response = client.get("/does-not-exist").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let _ = std::fs::remove_dir_all(serve_dir);
+}
@@ -0,0 +1,117 @@
+use fishnet::component::prelude::*;
+use fishnet::{Page, Website};
+
+mod common;
+use common::client_with;
+
+#[cfg(test)]
+use pretty_assertions::assert_eq;
+
+#[tokio::test]
+async fn test_sessions() {
+    use fishnet::session::{MemoryStore, Session};
+
+    #[dyn_component]
+    async fn session_counter() {
+        let state = state!(());
+
+        #[route("/", POST)]
+        async fn reset(session: Session) -> Markup {
+            session.clear();
+            html! { "reset" }
+        }
+
+        let visits = request()
+            .and_then(|request| request.session().cloned())
+            .map(|session| {
+                let visits = session.get_as::<usize>("visits").unwrap_or(0) + 1;
+                session.insert("visits", visits);
+                visits
+            })
+            .unwrap_or_default();
+
+        html! {
+            button hx-post=(state.endpoint()) { "visit no. " (visits) }
+        }
+    }
+
+    let home = || {
+        Page::new("home").with_body(|| {
+            async {
+                html! {
+                    (c!(session_counter()))
+                }
+            }
+            .boxed()
+        })
+    };
+    let client = client_with(Website::new().sessions(MemoryStore::new()), home()).await;
+
+    let get = |cookie: Option<&str>| {
+        let mut request = axum::http::Request::builder().uri("/");
+        if let Some(cookie) = cookie {
+            request = request.header("cookie", cookie);
+        }
+        request.body(axum::body::Body::empty()).unwrap()
+    };
+
+    let response = client.request(get(None)).await;
+    assert!(response.text().contains("visit no. 1"));
+    let cookie = response
+        .header("set-cookie")
+        .unwrap()
+        .split(';')
+        .next()
+        .unwrap()
+        .to_string();
+    assert!(cookie.starts_with("fishnet-session="));
+
+    // other visitors get their own session
+    let response = client.request(get(None)).await;
+    assert!(response.text().contains("visit no. 1"));
+
+    let response = client.request(get(Some(&cookie))).await;
+    assert!(response.text().contains("visit no. 2"));
+    assert_eq!(response.header("set-cookie"), None);
+    let response = client.request(get(Some(&cookie))).await;
+    assert!(response.text().contains("visit no. 3"));
+
+    let endpoint = response.attr("hx-post").unwrap().to_string();
+    let response = client
+        .request(
+            axum::http::Request::builder()
+                .method("POST")
+                .uri(&endpoint)
+                .header("cookie", &cookie)
+                .body(axum::body::Body::empty())
+                .unwrap(),
+        )
+        .await;
+    assert_eq!(response.text(), "reset");
+    assert!(response.header("set-cookie").unwrap().contains("Max-Age=0"));
+
+    // the session was deleted, so the old cookie starts over
+    let response = client.request(get(Some(&cookie))).await;
+    assert!(response.text().contains("visit no. 1"));
+
+    // with a lifetime the cookie outlives the browser and is renewed whenever the session changes
+    let client = client_with(
+        Website::new()
+            .sessions(MemoryStore::new())
+            .session_lifetime(std::time::Duration::from_secs(60 * 60)),
+        home(),
+    )
+    .await;
+
+    let response = client.request(get(None)).await;
+    let set_cookie = response.header("set-cookie").unwrap();
+    assert!(set_cookie.ends_with("; Max-Age=3600"));
+    let cookie = set_cookie.split(';').next().unwrap().to_string();
+
+    let response = client.request(get(Some(&cookie))).await;
+    assert!(response.text().contains("visit no. 2"));
+    assert!(response
+        .header("set-cookie")
+        .unwrap()
+        .starts_with(&format!("{}; Path=/", cookie)));
+}
@@ -0,0 +1,143 @@
+use axum::http::StatusCode;
+use fishnet::component::prelude::*;
+use fishnet::testing::TestClient;
+use fishnet::{Page, Website};
+
+mod common;
+use common::{client, raw_get};
+
+#[cfg(test)]
+use pretty_assertions::assert_eq;
+
+#[tokio::test]
+async fn test_streaming_page() {
+    #[dyn_component]
+    async fn streamed_greeting() {
+        let name = request()
+            .and_then(|request| request.query("name"))
+            .unwrap_or_default();
+
+        html! {
+            "hello " (name)
+        }
+    }
+
+    let page = Page::new("streamed").streaming(true).with_body(|| {
+        async {
+            html! {
+                (c!(streamed_greeting()))
+            }
+        }
+        .boxed()
+    });
+
+    let client = client(page).await;
+
+    let response = client.get("/?name=fish").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.header("content-type"),
+        Some("text/html; charset=utf-8")
+    );
+    assert!(response
+        .text()
+        .contains("<template id=\"fishnet-stream-0\"></template>"));
+    assert!(response.text().contains("hello fish"));
+    assert!(response.text().ends_with("</html>"));
+}
+
+#[tokio::test]
+async fn test_sse() {
+    use axum::body::Body;
+    use axum::http::Request;
+    use futures::StreamExt;
+    use tower_service::Service;
+
+    #[component(sse)]
+    async fn live_feed() {
+        let state = state!(());
+
+        #[route("/", POST)]
+        async fn publish(state: Extension<ComponentState<()>>) -> Markup {
+            let clients = state.broadcast(html! { p { "new post" } });
+            html! { (clients) }
+        }
+
+        html! {
+            div hx-ext="sse" sse-connect=(state.sse_endpoint()) sse-swap="message" hx-post=(state.endpoint()) {}
+        }
+    }
+
+    let page = Page::new("feed").with_body(|| async { c!(live_feed()) }.boxed());
+    let router = Website::new().add_page("/", page).await.into_router();
+    let client = TestClient::from_router(router.clone());
+
+    let response = client.get("/").await;
+    let sse_endpoint = response.attr("sse-connect").unwrap().to_string();
+    let endpoint = response.attr("hx-post").unwrap().to_string();
+    assert_eq!(sse_endpoint, format!("{}/sse", endpoint));
+
+    // nobody is listening yet
+    assert_eq!(client.post_form(&endpoint, "").await.text(), "0");
+
+    let request = Request::get(&sse_endpoint).body(Body::empty()).unwrap();
+    let stream = router.clone().call(request).await.unwrap();
+    assert_eq!(
+        stream.headers().get("content-type").unwrap(),
+        "text/event-stream"
+    );
+    let mut events = stream.into_body().into_data_stream();
+
+    assert_eq!(client.post_form(&endpoint, "").await.text(), "1");
+    let event = events.next().await.unwrap().unwrap();
+    assert_eq!(String::from_utf8_lossy(&event), "data: <p>new post</p>\n\n");
+}
+
+#[tokio::test]
+async fn test_websocket() {
+    use futures::{SinkExt, StreamExt};
+    use tokio_tungstenite::tungstenite;
+
+    #[component]
+    async fn echo() {
+        let state = state_init!(Arc::new("echo".to_string()));
+
+        #[websocket("/ws")]
+        async fn echo_socket(mut socket: WebSocket, state: ComponentState<Arc<String>>) {
+            while let Some(Ok(Message::Text(text))) = socket.recv().await {
+                let reply = format!("{}: {}", state.as_str(), text);
+                if socket.send(Message::Text(reply)).await.is_err() {
+                    break;
+                }
+            }
+        }
+
+        html! {
+            div ws-connect=(format!("{}/ws", state.endpoint())) {}
+        }
+    }
+
+    let page = Page::new("echo").with_body(|| async { c!(echo()) }.boxed());
+    let website = Website::new().add_page("/", page).await;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(website.serve_listener(listener));
+
+    let response = raw_get(addr, "/").await;
+    let path = response
+        .split("ws-connect=\"")
+        .nth(1)
+        .and_then(|rest| rest.split('"').next())
+        .unwrap();
+
+    let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://{}{}", addr, path))
+        .await
+        .unwrap();
+    socket
+        .send(tungstenite::Message::Text("hello".into()))
+        .await
+        .unwrap();
+    let reply = socket.next().await.unwrap().unwrap();
+    assert_eq!(reply, tungstenite::Message::Text("echo: hello".into()));
+}
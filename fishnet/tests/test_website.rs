@@ -0,0 +1,382 @@
+use axum::http::StatusCode;
+use fishnet::component::prelude::*;
+use fishnet::testing::TestClient;
+use fishnet::{Page, Website};
+
+mod common;
+use common::client;
+
+#[cfg(test)]
+use pretty_assertions::assert_eq;
+
+#[tokio::test]
+async fn test_pattern_page() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static TAG_BUILDS: AtomicUsize = AtomicUsize::new(0);
+
+    #[component]
+    async fn blog_post(slug: &str) {
+        #[route("/")]
+        async fn title(state: Extension<ComponentState<Arc<String>>>) -> Markup {
+            html! { "title of " (state.as_str()) }
+        }
+
+        let state = state_init!(Arc::new(slug.to_string()));
+
+        html! {
+            h1 hx-get=(state.endpoint()) { (state.as_str()) }
+        }
+    }
+
+    #[component]
+    async fn tag_header(tag: &str) {
+        TAG_BUILDS.fetch_add(1, Ordering::SeqCst);
+        let tag = state_init!(Arc::new(tag.to_string()));
+        html! { "tag " (tag.as_str()) }
+    }
+
+    #[component]
+    async fn tag_follow() {
+        let state = state!(());
+
+        #[route("/", POST)]
+        async fn follow() -> Markup {
+            html! { "followed" }
+        }
+
+        html! {
+            button hx-post=(state.endpoint()) { "follow" }
+        }
+    }
+
+    let blog = Page::new("blog")
+        .components_per_params()
+        .with_param_body(|params| {
+            async move {
+                let slug = params.get("slug").unwrap_or_default().to_string();
+                html! {
+                    (c!(blog_post(&slug)))
+                }
+            }
+            .boxed()
+        });
+    let tags = Page::new("tags").with_param_body(|params| {
+        async move {
+            let tag = params.get("tag").unwrap_or_default().to_string();
+            html! {
+                (c!(tag_header(&tag)))
+                (c!(key = &tag, tag_header(&tag)))
+                (c!(tag_follow()))
+            }
+        }
+        .boxed()
+    });
+
+    let website = Website::new()
+        .add_page("/blog/:slug", blog)
+        .await
+        .add_page("/tags/:tag", tags)
+        .await;
+    let client = TestClient::new(website);
+
+    let response = client.get("/blog/first-post").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.text().contains(">first-post</h1>"));
+    assert!(response
+        .text()
+        .contains("href=\"/blog/first-post/style.css\""));
+    assert!(response
+        .text()
+        .contains("src=\"/blog/first-post/script.js\""));
+
+    let response = client.get("/blog/second-post").await;
+    assert!(response.text().contains(">second-post</h1>"));
+    let endpoint = response.attr("hx-get").unwrap().to_string();
+    assert!(endpoint.starts_with("/blog/second-post/api/BlogPost_"));
+
+    let response = client.get(&endpoint).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "title of second-post");
+
+    let response = client.get("/blog/second-post/style.css").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // by default, components are shared between all parameters unless they are keyed
+    let response = client.get("/tags/rust").await;
+    assert!(response.text().contains(">tag rust</div>"));
+    let response = client.get("/tags/web").await;
+    assert!(response.text().contains(">tag rust</div>"));
+    assert!(response.text().contains(">tag web</div>"));
+    assert_eq!(TAG_BUILDS.load(Ordering::SeqCst), 3);
+
+    // ...so their routes can't contain the parameters of the page that built them
+    let mut endpoints = Vec::new();
+    for tag in ["rust", "web"] {
+        let response = client.get(&format!("/tags/{}", tag)).await;
+        let endpoint = response.attr("hx-post").unwrap().to_string();
+        assert!(endpoint.starts_with("/tags/_/api/TagFollow_"));
+
+        let response = client.post_form(&endpoint, "").await;
+        assert_eq!(response.text(), "followed");
+        endpoints.push(endpoint);
+    }
+    assert_eq!(endpoints[0], endpoints[1]);
+}
+
+#[tokio::test]
+async fn test_request_context() {
+    #[dyn_component]
+    async fn request_greeting() {
+        // there is no request during the pre-render
+        let request = request();
+        let name = request
+            .as_ref()
+            .and_then(|request| request.query("name"))
+            .unwrap_or_default();
+        let theme = request
+            .as_ref()
+            .and_then(|request| request.cookie("theme"))
+            .unwrap_or("light")
+            .to_string();
+
+        html! {
+            "hello " (name) ", you like " (theme)
+        }
+    }
+
+    #[component]
+    async fn request_static() {
+        html! {
+            "static sees request: " (request().is_some())
+        }
+    }
+
+    let page = Page::new("request").with_body(|| {
+        async {
+            let language = request()
+                .and_then(|request| request.preferred_language(&["de", "en"]))
+                .unwrap_or("none");
+
+            html! {
+                p { (language) }
+                (c!(request_greeting()))
+                (c!(request_static()))
+            }
+        }
+        .boxed()
+    });
+
+    let client = client(page).await;
+
+    let request = axum::http::Request::builder()
+        .uri("/?name=fish")
+        .header("cookie", "theme=dark")
+        .header("accept-language", "de-DE,en;q=0.5")
+        .body(axum::body::Body::empty())
+        .unwrap();
+    let (with_request, without_request) = tokio::join!(client.request(request), client.get("/"));
+
+    assert!(with_request.text().contains("<p>de</p>"));
+    assert!(with_request.text().contains("hello fish, you like dark"));
+    assert!(with_request.text().contains("static sees request: false"));
+
+    assert!(without_request.text().contains("<p>none</p>"));
+    assert!(without_request.text().contains("hello , you like light"));
+}
+
+#[tokio::test]
+async fn test_error_pages() {
+    #[component]
+    async fn failing_button() {
+        #[route("/", POST)]
+        async fn fail() -> StatusCode {
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+
+        #[route("/html", POST)]
+        async fn fail_html() -> (StatusCode, Markup) {
+            (StatusCode::SERVICE_UNAVAILABLE, html! { "try again later" })
+        }
+
+        let state = state!(());
+
+        html! {
+            button hx-post=(state.endpoint()) { "fail" }
+        }
+    }
+
+    #[component]
+    async fn not_found_message() {
+        style!(css! {
+            color: gray;
+        });
+
+        html! {
+            "nothing here"
+        }
+    }
+
+    let home = Page::new("home").with_body(|| {
+        async {
+            html! {
+                (c!(failing_button()))
+            }
+        }
+        .boxed()
+    });
+    let not_found = Page::new("not found").with_body(|| {
+        async {
+            let path = request()
+                .map(|request| request.path().to_string())
+                .unwrap_or_default();
+
+            html! {
+                (c!(not_found_message())) ": " (path)
+            }
+        }
+        .boxed()
+    });
+    let error = Page::new("error").with_body(|| {
+        async {
+            let status = request()
+                .and_then(|request| request.error_status())
+                .map(|status| status.as_u16())
+                .unwrap_or_default();
+
+            html! {
+                "something went wrong: " (status)
+            }
+        }
+        .boxed()
+    });
+
+    let website = Website::new()
+        .add_page("/", home)
+        .await
+        .not_found_page(not_found)
+        .await
+        .error_page(error)
+        .await;
+    let client = TestClient::new(website);
+
+    let response = client.get("/missing").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert!(response.text().contains("nothing here</div>: /missing"));
+    assert!(response.text().contains("/_fishnet/not-found/style.css"));
+
+    let response = client.get("/_fishnet/not-found/style.css").await;
+    assert!(response.text().contains(".not-found-message"));
+
+    let response = client.get("/api/missing").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert!(response.text().contains("nothing here</div>: /api/missing"));
+
+    let endpoint = client.get("/").await.attr("hx-post").unwrap().to_string();
+
+    let response = client.post_form(&endpoint, "").await;
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(response.text().contains("something went wrong: 500"));
+
+    let response = client.post_form(&format!("{}/html", endpoint), "").await;
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(response.text(), "try again later");
+}
+
+#[tokio::test]
+async fn test_render_errors() {
+    let posts = Page::new("posts").try_with_param_body(|params| {
+        async move {
+            match params.get("slug") {
+                Some("hello") => Ok(html! { "hello world" }),
+                Some("broken") => Err(RenderError::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "database unavailable",
+                )),
+                _ => Err(RenderError::not_found()),
+            }
+        }
+        .boxed()
+    });
+    let not_found = Page::new("not found").with_body(|| {
+        async {
+            html! { "no such post" }
+        }
+        .boxed()
+    });
+    let error = Page::new("error").with_body(|| {
+        async {
+            html! { "please try again" }
+        }
+        .boxed()
+    });
+
+    let website = Website::new()
+        .add_page("/posts/:slug", posts)
+        .await
+        .not_found_page(not_found)
+        .await
+        .error_page(error)
+        .await;
+    let client = TestClient::new(website);
+
+    let response = client.get("/posts/hello").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.text().contains("hello world"));
+
+    let response = client.get("/posts/missing").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert!(response.text().contains("no such post"));
+
+    let response = client.get("/posts/broken").await;
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(response.text().contains("please try again"));
+    assert!(!response.text().contains("database unavailable"));
+}
+
+#[tokio::test]
+async fn test_page_meta() {
+    use fishnet::page::PageMeta;
+
+    let home = Page::new("home").with_body(|| {
+        async {
+            html! { "home" }
+        }
+        .boxed()
+    });
+    let about = Page::new("about")
+        .with_meta(PageMeta::new().title("about us").robots("noindex"))
+        .with_body(|| {
+            async {
+                html! { "about" }
+            }
+            .boxed()
+        });
+
+    let website = Website::new()
+        .default_meta(
+            PageMeta::new()
+                .title("fishnet")
+                .description("a website about fish")
+                .theme_color("#112233"),
+        )
+        .add_page("/", home)
+        .await
+        .add_page("/about", about)
+        .await;
+    let client = TestClient::new(website);
+
+    let response = client.get("/").await;
+    let head = response.text().split("</head>").next().unwrap();
+    assert!(head.contains("<title>fishnet</title>"));
+    assert!(head.contains("<meta name=\"description\" content=\"a website about fish\">"));
+    assert!(head.contains("<meta name=\"theme-color\" content=\"#112233\">"));
+    assert!(!head.contains("robots"));
+
+    let response = client.get("/about").await;
+    let head = response.text().split("</head>").next().unwrap();
+    assert!(head.contains("<title>about us</title>"));
+    assert!(head.contains("<meta property=\"og:title\" content=\"about us\">"));
+    assert!(head.contains("<meta name=\"description\" content=\"a website about fish\">"));
+    assert!(head.contains("<meta name=\"robots\" content=\"noindex\">"));
+}
@@ -0,0 +1,8 @@
+use fishnet::component::prelude::*;
+
+#[dyn_component(cache = "60s")]
+async fn cached_dynamic() {
+    html! {}
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use fishnet::component::prelude::*;
+
+#[component(tag = "img")]
+async fn image() {
+    html! {}
+}
+
+#[component(attrs = [("aria-label", "image"), ("class", "wide")])]
+async fn wide() {
+    html! {}
+}
+
+fn main() {}